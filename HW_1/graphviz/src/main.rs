@@ -3,8 +3,7 @@
 //  HW1 - Graphviz Automata Generator
 //  9/9/2020
 
-// write message to terminal on failure
-use std::io::Write;
+use automata::graphviz::Digraph;
 
 fn main() {
     // Initialize Vector to store user input
@@ -16,8 +15,7 @@ fn main() {
 
     // Check that vector lenghth is only one
     if input.len() != 1 {
-        writeln!(std::io::stderr(), "Usage: Enter comma seperated node names, NO SPACES!!!")
-            .unwrap();
+        eprintln!("Usage: Enter comma seperated node names, NO SPACES!!!");
         std::process::exit(1);
     }
 
@@ -26,33 +24,18 @@ fn main() {
     // Initialize new vector to store cleaned up user input
     let mut nodes = Vec::new();
     // Iterate though string, seperate on comma, and push to nodes vector
-    for x in nodestr.split(",") {
+    for x in nodestr.split(',') {
         nodes.push(x);
     }
 
-    // Initialize variable for lenght of nodes vector
-    let length = nodes.len();
-    // Graph header
-    println!("digraph {{");
-    // Orient graph left to right
-    println!("rankdir=LR;");
-    // Start node and first state
-    println!("start -> {};", nodes[0]);
+    // Start node and first state, the end state gets double circles
+    let mut graph = Digraph::new(nodes[0]);
+    graph.accept.push(nodes[nodes.len()-1].to_string());
     // Iterate through vector to get node names
-    for y in 1..length {
-        println!("{} -> {}", nodes[y-1], nodes[y]);
+    for pair in nodes.windows(2) {
+        graph.edge(pair[0], pair[1], None);
     }
 
-    // Formatting start arrow
-    println!("start [shape=point];");
-    // Iterat though vector to format nodes
-    for z in 1..(length) {
-        println!("{} [shape=circle];", nodes[z-1]);
-    }
-    
-    // Format end state with double circles
-    println!("{} [shape=circle, peripheries=2];", nodes[length-1]);
-    // Print closing bracket
-    println!("}}")
+    // Print the graph left to right
+    print!("{}", graph);
 }
-
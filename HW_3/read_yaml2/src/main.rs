@@ -15,78 +15,186 @@
 //! # Usage
 //! 
 //! ```
-//! program2_drc filename
+//! program2_drc [-m] [-n [-d]] [-t testfile] filename
+//! program2_drc [-m] union|intersection|difference|symdiff|concat filename1 filename2
+//! program2_drc [-m] complement|reverse|star filename
+//! program2_drc [-l length] analyze filename
+//! program2_drc [-k count] [-r] [--min-length min] [-l length] strings filename
 //! ```
 //! or
 //! ```
-//! cargo run filename
+//! cargo run [-m] [-n [-d]] [-t testfile] filename
 //! ```
 //! 
 //! where: `filename` is a yaml file containing the DFA definition
+//!
+//! With `union`, `intersection`, `difference` or `symdiff` the DFAs in the
+//! two files are combined into one (see the `automata::product` module),
+//! which is written to `stdout` as yaml followed by its Graphviz definition.
+//! The `difference` accepts the strings accepted by the first DFA and not the
+//! second. With `-m` the combined DFA is minimized first.
+//!
+//! In the same way `concat` writes a DFA for a string accepted by the first
+//! DFA followed by one accepted by the second, and `complement`, `reverse`
+//! and `star` write a DFA built from the single DFA in the file (see the
+//! `automata::closure` module).
+//!
+//! With `analyze` the DFA in the file is not changed, instead it is reported
+//! whether its language is empty or finite, how many strings of each length
+//! up to `length` (10 unless given with `-l` or `--length`) it accepts, the
+//! total if finite, and which states are unreachable or dead (see the
+//! `automata::analysis` module).
+//!
+//! With `strings` the first `count` (10 unless given with `-k` or `--count`)
+//! strings the DFA accepts are listed in shortlex order, shortest first.
+//! With `-r` (or `--rejected`) the strings it rejects are listed instead.
+//! Only strings of at least `min` and at most `length` characters are
+//! listed when those are given.
+//!
+//! With `-n` (or `--nfa`) the file contains an NFA instead (see the `automata::nfa` module
+//! for the format). The NFA is simulated directly unless `-d` (or `--determinize`)
+//! is given, in which case it is converted to a DFA first.
+//!
+//! With `-t` (or `--test`) every string in `testfile` is checked against its
+//! expected verdict (see the `automata::batch` module for the format) instead
+//! of asking for a string. The exit code is 1 if any of them fail.
 //! 
 //! # Output
 //! 
 //! To `stderr`: Debug display of the internal graph structure
 //! 
 //! To `stdout`: Graphviz definitions of the graph structure
+//!
+//! With `-m` (or `--minimize`) the minimized DFA is also written to `stdout`
+//! as yaml followed by its Graphviz definition.
 
-use serde::{Deserialize};
-use std::io::Write;
+use automata::{batch, Error, DFA, NFA, StateGraph};
+use automata::product::Operation;
 
 // *********************************************************************
-//// # Deterministic Finite Automata Structure
-//// 
-//// Create a structure that the YAML files will be deserialized into.
-//// Note the use of the `Deserialize` trait
-//// 
-#[derive(Debug, Deserialize)]
-struct DFA {
-
-    /// The set of characters comprising the alphabet
-    alphabet: Vec<char>,
-    /// State number (1 relative) for the start state
-    start: usize,
-    /// Set of accept states (1 relative)
-    accept: Vec<usize>,
-    /// Matrix of transitions, rows are states, columns characters in the alphabet
-    transitions: Vec<Vec<usize>>,    
+/// # What to do with the automaton
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Command {
+    /// Test strings against it
+    #[default]
+    Test,
+    /// Combine it with a second DFA
+    Product(Operation),
+    /// Follow it with a second DFA
+    Concat,
+    /// Accept the strings it rejects
+    Complement,
+    /// Accept the strings it accepts written backwards
+    Reverse,
+    /// Accept any number of the strings it accepts
+    Star,
+    /// Report on its language and states
+    Analyze,
+    /// List its first strings
+    Strings,
 }
 
-// *********************************************************************
-//// # Definition of a single state
-#[derive(Debug)]
-struct State {
-    /// Is this an accept state
-    accept_state: bool,
-    /// Set of transitions (0 relative)
-    transitions: Vec<usize>
+impl Command {
+    /// Return the number of yaml files the command reads
+    fn files(self) -> usize {
+        match self {
+            Command::Product(_) | Command::Concat => 2,
+            _ => 1,
+        }
+    }
 }
 
 // *********************************************************************
-//// # State based representation of the DFA
-#[derive(Debug)]
-struct StateGraph {
-    /// The set of characters comprising the alphabet
-    alphabet: Vec<char>,
-    /// State number (0 relative) for the start state
-    start_state: usize,
-    /// Vector of state objects
-    states: Vec<Box<State>>
+/// # Command line options
+#[derive(Debug, Default)]
+struct Options {
+    /// What to do with the automaton
+    command: Command,
+    /// Name of the yaml file containing the DFA
+    filename: String,
+    /// Name of the yaml file containing the second DFA of a command
+    other: Option<String>,
+    /// Print the minimized DFA
+    minimize: bool,
+    /// The file contains an NFA
+    nfa: bool,
+    /// Determinize the NFA before testing strings
+    determinize: bool,
+    /// File of strings and expected verdicts to test
+    testfile: Option<String>,
+    /// Longest strings to count or list
+    length: Option<usize>,
+    /// Shortest strings to list
+    min_length: Option<usize>,
+    /// Number of strings to list
+    count: Option<usize>,
+    /// List the rejected strings
+    rejected: bool,
 }
 
 // *********************************************************************
 fn main() {
 
-    // Get and validat the filename on the command line
-    let filename = get_filename(std::env::args());
+    // The exit code is 1 when the string is rejected or something went wrong
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("*****{}*****", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// *********************************************************************
+/// Load the automaton and test a string against it
+///
+/// Return Ok and whether the string was accepted
+fn run() -> Result<bool, Error> {
+
+    // Get and validat the filename and options on the command line
+    let options = get_options(std::env::args())?;
+
+    match options.command {
+        Command::Test => {}
+        Command::Analyze => return analyze(&options),
+        Command::Strings => return list_strings(&options),
+        _ => return build(&options),
+    }
 
     // Load the yaml file getting a Box pointing to a DFA
     // instance on the heap
-    let dfa = DFA::new_from_file(&filename);
+    let dfa = if options.nfa {
+        let nfa = NFA::new_from_file(&options.filename)?;
+        nfa.validate()?;
+
+        println!("{:?}", nfa);
+        nfa.write_graphviz();
+
+        // Simulate the NFA directly unless it is to be determinized
+        if !options.determinize && !options.minimize {
+            if let Some(testfile) = &options.testfile {
+                return run_tests(testfile, |sentence| nfa.test_sentence(sentence));
+            }
+            let vinput: String = get_inputstring()?.into_iter().collect();
+            let accepted = nfa.test_sentence(&vinput)?;
+            println!("*****String {}*****", if accepted {"Accepted"} else {"Rejected"});
+            return Ok(accepted);
+        }
+
+        // Show the NFA states that make up each DFA state
+        let (dfa, subsets) = nfa.subset_construction();
+        for (n, set) in subsets.iter().enumerate() {
+            eprintln!("\tq{} = {:?}", n+1, set);
+        }
+        dfa
+    } else {
+        DFA::new_from_file(&options.filename)?
+    };
     //dfa.print("DFA from yaml: ");
 
     // Validate the DFA
-    dfa.validate().expect("Validation Failure:");
+    dfa.validate()?;
 
     // Get a state structure for the DFA
     let state_graph = StateGraph::new_from_dfa(&dfa);
@@ -94,250 +202,248 @@ fn main() {
     println!("{:?}", state_graph);
     state_graph.write_graphviz();
 
+    // Minimize the DFA and show which states were merged
+    if options.minimize {
+        let (mindfa, merged) = dfa.minimize();
+        for (n, states) in merged.iter().enumerate() {
+            println!("# q{} <- {}", n+1,
+                     states.iter().map(|s| format!("q{}", s)).collect::<Vec<_>>().join(", "));
+        }
+        mindfa.write_yaml();
+        StateGraph::new_from_dfa(&mindfa).write_graphviz();
+    }
+
+    // Check every string in the test file
+    if let Some(testfile) = &options.testfile {
+        return run_tests(testfile, |sentence| state_graph.test_sentence(sentence));
+    }
+
     // Get String to Validate against DFA
-    let vinput = get_inputstring();
+    let vinput = get_inputstring()?;
     //println!("Test String as vec: {:?}", vinput);
 
     // Validate a test string and show its state transitions
-    state_graph.validate_string(&vinput);
+    validate_string(&state_graph, &vinput)
 }
 
 // *********************************************************************
-//// Return the filename passed as the first parameter
-fn get_filename(args: std::env::Args) -> String {
-
-    // Get the arguments as a vector
-    let args: Vec<String> = args.collect();
-
-    // Make sure only one argument was passed
-    if args.len() != 2 {
-        writeln!(std::io::stderr(), "Usage: hw1 dfafile")
-            .unwrap();
-        std::process::exit(1);
-    }    
-    args[1].to_string()    
+/// Load and validate the DFA in the yaml file
+fn load_dfa(filename: &str) -> Result<Box<DFA>, Error> {
+    let dfa = DFA::new_from_file(filename)?;
+    dfa.validate()?;
+    Ok(dfa)
 }
 
 // *********************************************************************
-//// Get Test String to Validate against DFA
-fn get_inputstring() -> Vec<char> {
-    let mut s1 = String::new();
-    println!("*****Enter String to Check*****");    // Ask user for test string
-    std::io::stdin().read_line(&mut s1).unwrap();
-    // Check that something was entered
-    if s1.len() <= 2 {      // <= 2 because Rust includes \r\n as chars ending user input
-        writeln!(std::io::stderr(), "Usage: Enter a string to validate")
-            .unwrap();
-        std::process::exit(1);
+/// Build a DFA from the DFAs in the files with the command and write it
+/// as yaml and Graphviz
+fn build(options: &Options) -> Result<bool, Error> {
+
+    let first = load_dfa(&options.filename)?;
+    let second = || load_dfa(options.other.as_deref().expect("second filename"));
+
+    let mut dfa = match options.command {
+        Command::Test | Command::Analyze | Command::Strings => unreachable!("not a build command"),
+        Command::Product(op) => first.product(&*second()?, op),
+        Command::Concat => first.concat(&*second()?),
+        Command::Complement => first.complement(),
+        Command::Reverse => first.reverse(),
+        Command::Star => first.star(),
+    };
+    if options.minimize {
+        dfa = dfa.minimize().0;
     }
-    //println!("Test String {:?}", s1);
-    let n = s1.chars().count() - 2;
-    let s2 = &s1[0..n];     // use slice to remove \r\n chars
-    let mut v = Vec::new();
-    for c in s2.chars() {
-        v.push(c);
-    }
-    v
+    dfa.validate()?;
+    dfa.write_yaml();
+    StateGraph::new_from_dfa(&dfa).write_graphviz();
+    Ok(true)
 }
 
 // *********************************************************************
-//// Implement the methods of the DFA structure
-impl DFA {
-
-    //// Create and return a DFA on the heap
-    //// 
-    //// Load the .yaml file specified into a DFA structure
-    //// on the heap and return a point to it via a Box.
-    fn new_from_file(filename: &str) -> Box<DFA> {
+/// Report on the language of the DFA in the file and its useless states
+fn analyze(options: &Options) -> Result<bool, Error> {
 
-        let f = std::fs::File::open(filename)
-                    .expect("Unable to open input");
+    let dfa = load_dfa(&options.filename)?;
+    let graph = StateGraph::new_from_dfa(&dfa);
 
-        // Deserialize into the heap and return the pointer
-        Box::new(serde_yaml::from_reader(f)
-                    .expect("Unable to parse yaml") )
+    println!("Language is empty: {}", if graph.is_empty() {"yes"} else {"no"});
+    println!("Language is finite: {}", if graph.is_finite() {"yes"} else {"no"});
+    println!("Accepted strings of each length:");
+    for (length, count) in graph.count_by_length(options.length.unwrap_or(10)).iter().enumerate() {
+        println!("\t{}: {}", length, count);
+    }
+    if let Some(total) = graph.count() {
+        println!("Total accepted strings: {}", total);
     }
 
-    //// Validate the correctness of the DFA
-    fn validate(&self) -> Result<(), String> {
-
-        // The number of characters in the alphabet should match the number
-        // of columns in each state row
-        for (rnum, row) in self.transitions.iter().enumerate() {
-
-            if row.len() != self.alphabet.len() {
-                return Err(format!("Wrong number of columns({}) in row {}, should be {}",
-                                    row.len(), rnum + 1, self.alphabet.len() ))
-            }
-        }
-
-        // Validate that all states in the transition table are valid
-        for (rnum, row) in self.transitions.iter().enumerate() {
-            for (cnum, state) in row.iter().enumerate() {
+    // States (0 relative) of the graph past those of the DFA were added
+    // for its undefined transitions
+    let name = |n: usize| if n < dfa.transitions.len() { format!("q{}", n+1) }
+                          else { format!("q{} (sink for undefined transitions)", n+1) };
+    let list = |states: Vec<bool>, want: bool| {
+        let names: Vec<String> = states.iter().enumerate()
+                                       .filter(|(_, &s)| s == want)
+                                       .map(|(n, _)| name(n))
+                                       .collect();
+        if names.is_empty() { "none".to_string() } else { names.join(", ") }
+    };
+    println!("Unreachable states: {}", list(graph.reachable_states(), false));
+    println!("Dead states: {}", list(graph.live_states(), false));
+    Ok(true)
+}
 
-                if *state as usize >  self.transitions.len() {
-                    return Err(format!("Invalid transition state({}) in row {}, column {}",
-                                        state, rnum + 1, cnum + 1 ))
-                }    
-            }
-        }
+// *********************************************************************
+/// List the first strings the DFA in the file accepts, or rejects, in shortlex order
+fn list_strings(options: &Options) -> Result<bool, Error> {
 
-        // The start and accept states must be valid
-        if self.start as usize > self.transitions.len() {
-            return Err(format!("Start state({}), is not valid", self.start))
-        }
+    let dfa = load_dfa(&options.filename)?;
+    let graph = StateGraph::new_from_dfa(&dfa);
 
-        for acc_state in self.accept.iter() {
-            if *acc_state as usize  > self.transitions.len() {
-                return Err(format!("Accept state({}), is not valid", acc_state))
-            }
-        }
-        Ok(())
+    let strings = graph.strings(!options.rejected, options.min_length.unwrap_or(0), options.length);
+    for sentence in strings.take(options.count.unwrap_or(10)) {
+        println!("<{}>", sentence);
     }
-    // //// Guess what this does!
-    // fn print(&self, s: &str) {
-    //     println!("{}: {:?}", s, self);
-    // }
+    Ok(true)
 }
 
 // *********************************************************************
-/// Implement the methods of the State Graph structure
-impl StateGraph<> {
-
-    //// Create a state graph from a DFA structure
-    fn new_from_dfa(dfa: &DFA) -> Box<StateGraph> {
-
-        // Create an empty graph object
-        let mut graph = Box::new(StateGraph{alphabet: dfa.alphabet.clone(), 
-                                            start_state: dfa.start - 1,
-                                            states: vec!() });
-
-        // Look through the transition table building state objects
-        for row in dfa.transitions.iter() {
-            let mut v = Box::new(State{accept_state: false, transitions: vec!()});
-            for col in row {
-                v.transitions.push(col-1);
-            } 
-            graph.states.push(v);
-        }    
-
-        // Set the accept states
-        for astate in dfa.accept.iter() {
-            graph.states[*astate - 1].accept_state = true;
-        }
-        graph
+/// Return the command named by a word on the command line
+fn command_named(word: &str) -> Option<Command> {
+    match word {
+        "union" => Some(Command::Product(Operation::Union)),
+        "intersection" => Some(Command::Product(Operation::Intersection)),
+        "difference" => Some(Command::Product(Operation::Difference)),
+        "symdiff" => Some(Command::Product(Operation::SymmetricDifference)),
+        "concat" => Some(Command::Concat),
+        "complement" => Some(Command::Complement),
+        "reverse" => Some(Command::Reverse),
+        "star" => Some(Command::Star),
+        "analyze" => Some(Command::Analyze),
+        "strings" => Some(Command::Strings),
+        _ => None,
     }
+}
 
-    //// Validate Test String and show State Transitions
-    fn validate_string(&self, v: &Vec<char>) {
-
-        // Quick check to make sure all chars in test string/vector are part of the alphabet
-        let alpha = &self.alphabet;
-        for i in 0..v.len() {
-            if alpha.contains(&v[i]) {
-                true;
+// *********************************************************************
+/// Return the filename and options passed on the command line
+fn get_options(args: std::env::Args) -> Result<Options, Error> {
+
+    let mut options = Options::default();
+    let mut filenames = Vec::new();
+    let mut valid = true;
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--minimize" => options.minimize = true,
+            "-n" | "--nfa" => options.nfa = true,
+            "-d" | "--determinize" => options.determinize = true,
+            "-t" | "--test" => {
+                options.testfile = args.next();
+                valid &= options.testfile.is_some();
             }
-            else {
-                if let Err(msg) = return_result(false) {
-                    println!("{} String contains Characters that are not in the alphabet: {:?}", msg, alpha);
-                    std::process::exit(1);
-                }
+            "-l" | "--length" => {
+                options.length = args.next().and_then(|n| n.parse().ok());
+                valid &= options.length.is_some();
             }
-        }
-        
-        // Loop through test string/vector and StateGraph to validate each transition
-        let mut curr = &self.states[0];
-        let mut m = 0;
-        for i in 0..v.len() {
-            for (n, state) in self.states.iter().enumerate() {
-                //println!("n = {}, m = {}", n, m);
-                // We see a X and n = state we are transitioning from
-                if v[i] == alpha[0] && n == m {
-                    println!("\t\u{03B4} (q{}, {}) -> q{}", m+1, v[i], state.transitions[0]+1);
-                    m = state.transitions[0];
-                    curr = &self.states[state.transitions[0]];
-                    break;
-                }
-                // We see a Y and n = state we are transitioning from
-                if v[i] == alpha[1] && n == m {
-                    println!("\t\u{03B4} (q{}, {}) -> q{}", m+1, v[i], state.transitions[1]+1);
-                    m = state.transitions[1];
-                    curr = &self.states[state.transitions[1]];
-                    break;
-                }
+            "--min-length" => {
+                options.min_length = args.next().and_then(|n| n.parse().ok());
+                valid &= options.min_length.is_some();
             }
-        }
-        // String Accepted
-        if curr.accept_state == true {
-            println!("*****String Accepted*****");
-        }
-        // String Rejected
-        else {
-            if let Err(msg) = return_result(false) {
-                println!("{} String ends in a Non Accepting State", msg);
-                std::process::exit(1);
+            "-k" | "--count" => {
+                options.count = args.next().and_then(|n| n.parse().ok());
+                valid &= options.count.is_some();
             }
+            "-r" | "--rejected" => options.rejected = true,
+            // A command must come before the filenames
+            _ => match command_named(&arg) {
+                Some(command) if filenames.is_empty() && options.command == Command::Test => {
+                    options.command = command;
+                }
+                _ => filenames.push(arg),
+            },
         }
     }
 
-    //// Write the graph to stdout
-    fn write_graphviz(&self) {
-
-        println!("digraph {{");
-        println!("\trankdir=LR;");
-        println!("\tnode [shape=point]; start;");
-        
-        for (n, state) in self.states.iter().enumerate() {
-            if state.accept_state {
-                println!("\tnode [shape=doublecircle]; q{};", n+1);
-            }
-        }
-        
-        println!("\tnode [shape=circle];");
-        println!("\tstart -> q{}", self.start_state+1);
-
-        for (n, state) in self.states.iter().enumerate() {
-
-            for (i, ch) in self.alphabet.iter().enumerate() {
-                println!("\tq{} -> q{} [label=\"{}\"];", n+1, state.transitions[i] + 1, ch);
-            }
-        }
-
-        println!("}}");
+    // Make sure one filename was passed, or two for a product or concat
+    valid &= filenames.len() == options.command.files();
+    valid &= options.command == Command::Test || (!options.nfa && options.testfile.is_none());
+    valid &= options.length.is_none() || options.command == Command::Analyze
+                                      || options.command == Command::Strings;
+    valid &= (options.min_length.is_none() && options.count.is_none() && !options.rejected)
+             || options.command == Command::Strings;
+    if !valid || (options.determinize && !options.nfa) {
+        return Err(Error::Usage(
+            "program2_drc [-m] [-n [-d]] [-t testfile] dfafile \
+             | program2_drc [-m] union|intersection|difference|symdiff|concat dfafile dfafile \
+             | program2_drc [-m] complement|reverse|star dfafile \
+             | program2_drc [-l length] analyze dfafile \
+             | program2_drc [-k count] [-r] [--min-length min] [-l length] strings dfafile".to_string()));
+    }
+    if filenames.len() == 2 {
+        options.other = filenames.pop();
     }
+    options.filename = filenames.pop().unwrap();
+    Ok(options)
+}
 
-    // Result Method
+// *********************************************************************
+/// Check each string in the test file against its expected verdict
+///
+/// Print the failures and a summary, return Ok and whether all passed
+fn run_tests<F>(testfile: &str, test: F) -> Result<bool, Error> where F: Fn(&str) -> Result<bool, Error> {
+    let summary = batch::run_cases(&batch::read_cases(testfile)?, test);
+    println!("{}", summary);
+    Ok(summary.passed())
 }
 
-// Result Method
-fn return_result(tf : bool) -> Result<&'static str, &'static str> {    
-    if tf {
-        Ok("***ACCEPTED***")
-    } else {
-        Err("*****String REJECTED*****")
+// *********************************************************************
+/// Get Test String to Validate against DFA
+fn get_inputstring() -> Result<Vec<char>, Error> {
+    let mut s1 = String::new();
+    println!("*****Enter String to Check*****");    // Ask user for test string
+    std::io::stdin().read_line(&mut s1)?;
+    // Remove the \r\n chars ending user input
+    let s2 = s1.trim_end_matches(&['\r', '\n'][..]);
+    // Check that something was entered
+    if s2.is_empty() {
+        return Err(Error::Usage("Enter a string to validate".to_string()));
     }
+    Ok(s2.chars().collect())
 }
 
-#[test]
-fn result_1() {
-    // Get an Ok return code from the test function
-    assert!(match return_result(true) {
-                Ok(msg) => {
-                    println!("Ok: {}", msg);
-                    true
-                }
-                Err(_) => false
-            });
+// *********************************************************************
+/// Validate Test String and show State Transitions
+///
+/// Return Err if the string contains a character not in the alphabet,
+/// otherwise Ok and whether the string was accepted.
+fn validate_string(graph: &StateGraph, v: &[char]) -> Result<bool, Error> {
+
+    // Trace the test string/vector, any character not part of the alphabet stops it
+    let steps = graph.trace(v)?;
+
+    // Show each transition
+    for step in steps.iter() {
+        println!("\t\u{03B4} (q{}, {}) -> q{}", step.from+1, step.symbol, step.to+1);
+    }
+    let last = steps.last().map_or(graph.start_state, |step| step.to);
+
+    // String Accepted
+    if graph.states[last].accept_state {
+        println!("*****String Accepted*****");
+        Ok(true)
+    }
+    // String Rejected
+    else {
+        println!("*****String REJECTED***** String ends in a Non Accepting State");
+        Ok(false)
+    }
 }
 
 #[test]
-fn result_2() {
-    // Get an Err return code from the test function
-    assert!(match return_result(false) {
-                Err(msg) => {
-                    println!("Err: {}", msg);
-                    true
-                }
-                Ok(_) => false
-            });
-}
\ No newline at end of file
+fn validate_unknown_symbol() {
+    let dfa = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![2, 2]]);
+    let graph = StateGraph::new_from_dfa(&dfa);
+    assert!(validate_string(&graph, &['a', 'b']).unwrap());
+    assert!(!validate_string(&graph, &['a']).unwrap());
+    assert!(matches!(validate_string(&graph, &['a', 'c']), Err(Error::UnknownSymbol('c'))));
+}
@@ -0,0 +1,157 @@
+//! Analysis of the language accepted by a DFA
+//!
+//! These are graph searches on a [`StateGraph`]. A state is unreachable if
+//! no string leads to it from the start state, and dead if no accept state
+//! can be reached from it. The language is empty when the start state is
+//! dead, and infinite when a cycle goes through states that are neither.
+//!
+//! Strings are counted by the characters they are made of, so a symbol
+//! such as `a-z` stands for 26 different characters. Counts saturate at
+//! `u128::MAX`.
+
+use crate::graph::StateGraph;
+
+// *********************************************************************
+/// Implement the language analysis on the State Graph structure
+impl StateGraph {
+
+    /// Return the states (0 relative) that can be reached from the start state
+    pub fn reachable_states(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.states.len()];
+        let mut stack = vec![self.start_state];
+        reachable[self.start_state] = true;
+        while let Some(state) = stack.pop() {
+            for &to in self.states[state].transitions.iter() {
+                if !reachable[to] {
+                    reachable[to] = true;
+                    stack.push(to);
+                }
+            }
+        }
+        reachable
+    }
+
+    // *********************************************************************
+    /// Is the language accepted by the graph empty
+    pub fn is_empty(&self) -> bool {
+        !self.live_states()[self.start_state]
+    }
+
+    // *********************************************************************
+    /// Is the language accepted by the graph finite
+    pub fn is_finite(&self) -> bool {
+
+        // Only states that are reachable and not dead lie on the path of an
+        // accepted string, the language is infinite if they have a cycle
+        let live = self.live_states();
+        let useful: Vec<bool> = self.reachable_states().iter().zip(&live).map(|(&r, &l)| r && l).collect();
+
+        // Keep removing useful states that no remaining useful state leads
+        // to, a cycle (or a loop on one state) keeps some of them
+        let mut incoming = vec![0; self.states.len()];
+        for state in self.states.iter().zip(&useful).filter(|(_, &u)| u).map(|(s, _)| s) {
+            for &to in state.transitions.iter().filter(|&&to| useful[to]) {
+                incoming[to] += 1;
+            }
+        }
+        let mut ready: Vec<usize> = (0..self.states.len()).filter(|&n| useful[n] && incoming[n] == 0).collect();
+        let mut removed = 0;
+        while let Some(state) = ready.pop() {
+            removed += 1;
+            for &to in self.states[state].transitions.iter().filter(|&&to| useful[to]) {
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    ready.push(to);
+                }
+            }
+        }
+        removed == useful.iter().filter(|&&u| u).count()
+    }
+
+    // *********************************************************************
+    /// Return the number of accepted strings of each length from 0 to max
+    pub fn count_by_length(&self, max: usize) -> Vec<u128> {
+
+        // Number of characters in each class
+        let mut sizes = vec![0u128; self.states.first().map_or(0, |s| s.transitions.len())];
+        for (col, symbol) in self.alphabet.iter().enumerate() {
+            sizes[self.columns[col]] += u128::from(symbol.size());
+        }
+
+        // Number of strings of the current length that lead to each state
+        let mut ways = vec![0u128; self.states.len()];
+        ways[self.start_state] = 1;
+        let mut counts = Vec::with_capacity(max + 1);
+        for length in 0..=max {
+            counts.push(self.states.iter().zip(&ways)
+                            .filter(|(state, _)| state.accept_state)
+                            .fold(0u128, |total, (_, &w)| total.saturating_add(w)));
+            if length == max {
+                break;
+            }
+            let mut next = vec![0u128; self.states.len()];
+            for (state, &w) in self.states.iter().zip(&ways).filter(|(_, &w)| w > 0) {
+                for (class, &to) in state.transitions.iter().enumerate() {
+                    next[to] = next[to].saturating_add(w.saturating_mul(sizes[class]));
+                }
+            }
+            ways = next;
+        }
+        counts
+    }
+
+    // *********************************************************************
+    /// Return the number of accepted strings, None if there are infinitely many
+    pub fn count(&self) -> Option<u128> {
+        if !self.is_finite() {
+            return None;
+        }
+        // Without a cycle no accepted string is longer than the number of states
+        Some(self.count_by_length(self.states.len()).iter()
+                 .fold(0u128, |total, &c| total.saturating_add(c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{StateGraph, DFA};
+    use crate::testing::regex_graph;
+
+    #[test]
+    fn finite_languages() {
+        let graph = regex_graph("a|bc?|[x-z]{2}");
+        assert!(!graph.is_empty());
+        assert!(graph.is_finite());
+        assert_eq!(graph.count_by_length(3), vec![0, 2, 10, 0]);
+        assert_eq!(graph.count(), Some(12));
+        assert_eq!(regex_graph("x{0}").count(), Some(1));
+        assert_eq!(regex_graph("é|中").count_by_length(1), vec![0, 2]);
+        assert_eq!(regex_graph("[\u{D7FF}-\u{E000}]").count(), Some(2));
+    }
+
+    #[test]
+    fn infinite_languages() {
+        let graph = regex_graph("(ab)*|c");
+        assert!(!graph.is_finite());
+        assert_eq!(graph.count(), None);
+        assert_eq!(graph.count_by_length(4), vec![1, 1, 1, 0, 1]);
+        assert_eq!(regex_graph("[a-z]*").count_by_length(2), vec![1, 26, 676]);
+        // The loops on the dead sink state do not count
+        assert_eq!(regex_graph("ab").count(), Some(1));
+    }
+
+    #[test]
+    fn states() {
+        // State 3 can not be reached, state 4 is dead
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![2, 4], vec![4, 4], vec![2, 1], vec![4, 4]]);
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert_eq!(graph.reachable_states(), vec![true, true, false, true]);
+        assert_eq!(graph.live_states(), vec![true, true, true, false]);
+        assert!(graph.is_finite());
+        assert_eq!(graph.count(), Some(1));
+        let none = StateGraph::new_from_dfa(&DFA::new(vec!['a'], 1, vec![], vec![vec![1]]));
+        assert!(none.is_empty());
+        assert!(none.is_finite());
+        assert_eq!(none.count(), Some(0));
+    }
+}
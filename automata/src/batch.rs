@@ -0,0 +1,127 @@
+//! Batch testing against a file of expected verdicts
+//!
+//! Each line of a test file is `accept` or `reject`, a single space and the
+//! string to test, which runs to the end of the line and may itself contain
+//! spaces. `accept` or `reject` alone tests the empty string. Blank lines
+//! and lines starting with `#` are skipped.
+//!
+//! ```text
+//! # strings ending in b
+//! accept ab
+//! reject ba
+//! reject
+//! ```
+
+use crate::error::Error;
+use std::fmt;
+
+// *********************************************************************
+/// # One line of a test file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    /// Line number (1 relative) in the test file
+    pub line: usize,
+    /// Should the string be accepted
+    pub accept: bool,
+    /// The string to test
+    pub sentence: String,
+}
+
+// *********************************************************************
+/// # Result of running every case of a test file
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// Number of cases run
+    pub total: usize,
+    /// Cases that did not get the expected verdict
+    pub failures: Vec<Case>,
+}
+
+// *********************************************************************
+/// Parse the text of a test file into its cases
+pub fn parse_cases(text: &str) -> Result<Vec<Case>, Error> {
+
+    let mut cases = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (verdict, sentence) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+        let accept = match verdict {
+            "accept" => true,
+            "reject" => false,
+            _ => return Err(Error::TestCase{line: n + 1, text: line.to_string()}),
+        };
+        cases.push(Case{line: n + 1, accept, sentence: sentence.to_string()});
+    }
+    Ok(cases)
+}
+
+// *********************************************************************
+/// Read and parse a test file
+pub fn read_cases(filename: &str) -> Result<Vec<Case>, Error> {
+    parse_cases(&std::fs::read_to_string(filename)?)
+}
+
+// *********************************************************************
+/// Run every case through `test` and collect the ones that fail
+///
+/// A string with a character that is not in the alphabet is not in the
+/// language, so an error from `test` counts as a reject.
+pub fn run_cases<F>(cases: &[Case], test: F) -> Summary where F: Fn(&str) -> Result<bool, Error> {
+    let failures = cases.iter()
+                        .filter(|case| test(&case.sentence).unwrap_or(false) != case.accept)
+                        .cloned()
+                        .collect();
+    Summary{total: cases.len(), failures}
+}
+
+impl Summary {
+    /// Did every case pass
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for case in self.failures.iter() {
+            writeln!(f, "FAIL line {}: expected {} <{}>", case.line,
+                     if case.accept {"Accept"} else {"Reject"}, case.sentence)?;
+        }
+        write!(f, "*****Passed {} of {} tests, {} failed*****",
+               self.total - self.failures.len(), self.total, self.failures.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines() {
+        let cases = parse_cases("# comment\n\naccept ab\r\nreject\nreject a b\n").unwrap();
+        assert_eq!(cases, vec![Case{line: 3, accept: true, sentence: "ab".to_string()},
+                               Case{line: 4, accept: false, sentence: "".to_string()},
+                               Case{line: 5, accept: false, sentence: "a b".to_string()}]);
+        assert!(matches!(parse_cases("accept a\nmaybe b"), Err(Error::TestCase{line: 2, ..})));
+    }
+
+    #[test]
+    fn summary() {
+        let cases = parse_cases("accept b\naccept ab\nreject a\naccept c\n").unwrap();
+        let summary = run_cases(&cases, |s| {
+            if s.contains('c') {
+                return Err(Error::UnknownSymbol('c'));
+            }
+            Ok(s.ends_with('b'))
+        });
+        assert!(!summary.passed());
+        assert_eq!(summary.failures.iter().map(|c| c.line).collect::<Vec<_>>(), vec![4]);
+        assert!(summary.to_string().ends_with("Passed 3 of 4 tests, 1 failed*****"));
+    }
+}
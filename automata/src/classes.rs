@@ -0,0 +1,123 @@
+//! Alphabet equivalence classes
+//!
+//! Symbols that lead to the same state from every state of a DFA can share
+//! one column of the transition table. A [`ClassMap`] finds the class of a
+//! character with a table lookup for ASCII and a binary search of the
+//! sorted ranges for everything else.
+
+use crate::symbol::Symbol;
+
+/// Number of characters looked up directly in a table
+const TABLE_SIZE: usize = 128;
+
+// *********************************************************************
+/// # Map from characters to equivalence classes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMap {
+    /// Class of each ASCII character, None if it is not in the alphabet
+    table: Vec<Option<usize>>,
+    /// Sorted, disjoint ranges above ASCII with their class
+    ranges: Vec<(Symbol, usize)>,
+}
+
+impl ClassMap {
+
+    /// Create the map giving every character of each symbol the class at the
+    /// same position of classes
+    pub fn new(alphabet: &[Symbol], classes: &[usize]) -> ClassMap {
+
+        let mut map = ClassMap{table: vec![None; TABLE_SIZE], ranges: Vec::new()};
+        let mut ranges = Vec::new();
+        for (symbol, &class) in alphabet.iter().zip(classes) {
+            for code in symbol.lo as usize..=(symbol.hi as usize).min(TABLE_SIZE - 1) {
+                map.table[code] = Some(class);
+            }
+            if symbol.hi as usize >= TABLE_SIZE {
+                let lo = symbol.lo.max(TABLE_SIZE as u8 as char);
+                ranges.push((Symbol::new(lo, symbol.hi), class));
+            }
+        }
+        ranges.sort_unstable();
+
+        // Join neighboring ranges of the same class
+        for (symbol, class) in ranges {
+            match map.ranges.last_mut() {
+                Some((last, c)) if *c == class && last.hi as u32 + 1 == symbol.lo as u32 => {
+                    last.hi = symbol.hi;
+                }
+                _ => map.ranges.push((symbol, class)),
+            }
+        }
+        map
+    }
+
+    // *********************************************************************
+    /// Return the class of the character, None if it is not in the alphabet
+    pub fn class(&self, ch: char) -> Option<usize> {
+        if (ch as usize) < TABLE_SIZE {
+            return self.table[ch as usize];
+        }
+        self.ranges
+            .binary_search_by(|(symbol, _)| {
+                if symbol.hi < ch {
+                    std::cmp::Ordering::Less
+                } else if symbol.lo > ch {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .ok()
+            .map(|i| self.ranges[i].1)
+    }
+}
+
+// *********************************************************************
+/// Group the columns of a transition table that are the same in every row
+///
+/// Return the class of each column, classes are numbered in the order
+/// their first column appears.
+pub fn group_columns(rows: &[Vec<usize>], width: usize) -> Vec<usize> {
+    let mut firsts: Vec<usize> = Vec::new();    // first column of each class
+    let mut classes = Vec::with_capacity(width);
+    for col in 0..width {
+        let same = |other: usize| rows.iter().all(|row| row[col] == row[other]);
+        match firsts.iter().position(|&first| same(first)) {
+            Some(class) => classes.push(class),
+            None => {
+                classes.push(firsts.len());
+                firsts.push(col);
+            }
+        }
+    }
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let alphabet = vec![Symbol::new('a', 'z'), Symbol::from('0'), Symbol::new('å', 'é'),
+                            Symbol::from('中'), Symbol::new('{', 'ä')];
+        let map = ClassMap::new(&alphabet, &[0, 1, 2, 0, 2]);
+        assert_eq!(map.class('m'), Some(0));
+        assert_eq!(map.class('0'), Some(1));
+        assert_eq!(map.class('1'), None);
+        assert_eq!(map.class('{'), Some(2));
+        assert_eq!(map.class('\u{80}'), Some(2));
+        assert_eq!(map.class('ä'), Some(2));
+        assert_eq!(map.class('é'), Some(2));
+        assert_eq!(map.class('ê'), None);
+        assert_eq!(map.class('中'), Some(0));
+        assert_eq!(map.ranges.len(), 2);
+    }
+
+    #[test]
+    fn columns() {
+        let rows = vec![vec![1, 0, 1, 2], vec![1, 1, 1, 1]];
+        assert_eq!(group_columns(&rows, 4), vec![0, 1, 0, 2]);
+        assert_eq!(group_columns(&[], 3), vec![0, 0, 0]);
+    }
+}
@@ -0,0 +1,143 @@
+//! Closure operations on DFAs
+//!
+//! The regular languages are closed under complement, reversal,
+//! concatenation and Kleene star. The complement only needs the accept
+//! states of a complete DFA flipped. The others are built as an NFA with
+//! epsilon transitions joining the DFAs, which is then determinized with
+//! the subset construction.
+
+use crate::{DFA, NFA};
+use crate::symbol;
+
+// *********************************************************************
+/// Implement the closure operations on the DFA structure
+impl DFA {
+
+    /// Return the NFA with the same states and transitions
+    pub fn to_nfa(&self) -> NFA {
+        NFA{alphabet: self.alphabet.clone(),
+            start: self.start,
+            accept: self.accept.clone(),
+            transitions: self.transitions.iter()
+                             .map(|row| row.iter().map(|t| t.iter().copied().collect()).collect())
+                             .collect(),
+            epsilon: vec![vec!(); self.transitions.len()],
+            assertions: vec!()}
+    }
+
+    // *********************************************************************
+    /// Return a DFA accepting the strings over the alphabet this one rejects
+    ///
+    /// The DFA is completed first, so the strings that take an undefined
+    /// transition are accepted by the complement. A string with a character
+    /// outside the alphabet is still rejected.
+    pub fn complement(&self) -> Box<DFA> {
+        let mut dfa = self.complete();
+        dfa.accept = (1..=dfa.transitions.len()).filter(|s| !self.accept.contains(s)).collect();
+        dfa
+    }
+
+    // *********************************************************************
+    /// Return a DFA accepting the reverse of every string this one accepts
+    ///
+    /// Every transition is turned around in an NFA whose added start state
+    /// has an epsilon transition to each accept state.
+    pub fn reverse(&self) -> Box<DFA> {
+
+        let n_states = self.transitions.len();
+        let mut nfa = NFA{alphabet: self.alphabet.clone(),
+                          start: n_states + 1,
+                          accept: vec![self.start],
+                          transitions: vec![vec![vec!(); self.alphabet.len()]; n_states + 1],
+                          epsilon: vec![vec!(); n_states + 1],
+                          assertions: vec!()};
+        for (from, row) in self.transitions.iter().enumerate() {
+            for (col, to) in row.iter().enumerate() {
+                if let Some(to) = to {
+                    nfa.transitions[to - 1][col].push(from + 1);
+                }
+            }
+        }
+        nfa.epsilon[n_states] = self.accept.clone();
+        nfa.subset_construction().0
+    }
+
+    // *********************************************************************
+    /// Return a DFA accepting a string accepted by this DFA followed by
+    /// one accepted by the other
+    ///
+    /// The alphabets are aligned first, as for [`DFA::product`].
+    pub fn concat(&self, other: &DFA) -> Box<DFA> {
+
+        let alphabet = symbol::union(&self.alphabet, &other.alphabet);
+        let mut nfa = self.with_alphabet(&alphabet).to_nfa();
+        let second = other.with_alphabet(&alphabet).to_nfa();
+
+        // The states of the other DFA follow those of this one
+        let shift = nfa.transitions.len();
+        for &accept in self.accept.iter() {
+            nfa.epsilon[accept - 1].push(second.start + shift);
+        }
+        nfa.transitions.extend(second.transitions.iter().map(|row| {
+            row.iter().map(|cell| cell.iter().map(|s| s + shift).collect()).collect()
+        }));
+        nfa.epsilon.extend(second.epsilon);
+        nfa.accept = second.accept.iter().map(|s| s + shift).collect();
+        nfa.subset_construction().0
+    }
+
+    // *********************************************************************
+    /// Return a DFA accepting any number of strings accepted by this one,
+    /// one after the other
+    ///
+    /// An added accepting start state has an epsilon transition to the old
+    /// start state, as does each accept state.
+    pub fn star(&self) -> Box<DFA> {
+
+        let mut nfa = self.to_nfa();
+        for &accept in self.accept.iter() {
+            nfa.epsilon[accept - 1].push(self.start);
+        }
+        nfa.transitions.push(vec![vec!(); self.alphabet.len()]);
+        nfa.epsilon.push(vec![self.start]);
+        nfa.start = nfa.transitions.len();
+        nfa.accept.push(nfa.start);
+        nfa.subset_construction().0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{regex_dfa, same};
+
+    #[test]
+    fn complement() {
+        // Strings of a's followed by a single b, with the rest undefined
+        let dfa = DFA{alphabet: symbol::singles(&['a', 'b']), start: 1, accept: vec![2],
+                      transitions: vec![vec![Some(1), Some(2)], vec![None, None]]};
+        assert!(same(&dfa.complement(), "[ab]*b[ab]+|a*"));
+        assert!(same(&dfa.complement().complement(), "a*b"));
+        assert!(dfa.complement().is_complete());
+    }
+
+    #[test]
+    fn reverse() {
+        assert!(same(&regex_dfa("ab*c").reverse(), "cb*a"));
+        assert!(same(&regex_dfa("(a|bc)*d").reverse(), "d(a|cb)*"));
+        assert!(same(&regex_dfa("x{0}").reverse(), "x{0}"));
+        let none = DFA::new(vec!['a'], 1, vec![], vec![vec![1]]);
+        assert!(none.reverse().distinguish(&none).is_none());
+    }
+
+    #[test]
+    fn concat_and_star() {
+        assert!(same(&regex_dfa("a*").concat(&regex_dfa("b|c")), "a*(b|c)"));
+        assert!(same(&regex_dfa("ab|a").concat(&regex_dfa("ba|a")), "aba|aa|abba|aba"));
+        assert!(same(&regex_dfa("ab").star(), "(ab)*"));
+        assert!(same(&regex_dfa("a*b").star(), "(a*b)*"));
+        let none = DFA::new(vec!['a'], 1, vec![], vec![vec![1]]);
+        assert!(same(&none.star(), "a{0}"));
+        assert!(none.concat(&regex_dfa("a")).distinguish(&none).is_none());
+    }
+}
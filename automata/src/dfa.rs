@@ -0,0 +1,308 @@
+//! Deterministic finite automata as read from yaml
+//!
+//! A DFA may be partial: a transition written as `~` (yaml null) is
+//! undefined, and a string that takes it is rejected. [`DFA::complete`]
+//! sends every undefined transition to an added sink state.
+//!
+//! ```yaml
+//! alphabet: ['a', 'b']
+//! start: 1
+//! accept: [2]
+//! transitions:
+//!   - [2, ~]
+//!   - [~, 2]
+//! ```
+
+use crate::NFA;
+use crate::error::{Error, Location};
+use crate::regex;
+use crate::symbol::{self, Symbol};
+use serde::{Deserialize};
+
+// *********************************************************************
+/// # Definition of a DFA
+///
+/// The structure the yaml files are deserialized into.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DFA {
+    /// The set of symbols comprising the alphabet
+    pub alphabet: Vec<Symbol>,
+    /// State number (1 relative) for the start state
+    pub start: usize,
+    /// Set of accept states (1 relative)
+    pub accept: Vec<usize>,
+    /// Matrix of transitions, rows are states, columns characters in the alphabet,
+    /// None where the transition is undefined
+    pub transitions: Vec<Vec<Option<usize>>>,
+}
+
+// *********************************************************************
+/// Implement the methods of the DFA structure
+impl DFA {
+
+    /// Create a complete DFA from a transition matrix with every transition defined
+    pub fn new<S: Into<Symbol>>(alphabet: Vec<S>, start: usize, accept: Vec<usize>,
+                                transitions: Vec<Vec<usize>>) -> DFA {
+        let alphabet = alphabet.into_iter().map(Into::into).collect();
+        let transitions = transitions.into_iter()
+                                     .map(|row| row.into_iter().map(Some).collect())
+                                     .collect();
+        DFA{alphabet, start, accept, transitions}
+    }
+
+    // *********************************************************************
+    /// Create and return a DFA on the heap
+    ///
+    /// Load the .yaml file specified into a DFA structure
+    /// on the heap and return a point to it via a Box.
+    pub fn new_from_file(filename: &str) -> Result<Box<DFA>, Error> {
+
+        let f = std::fs::File::open(filename)?;
+
+        // Deserialize into the heap and return the pointer
+        Ok(Box::new(serde_yaml::from_reader(f)?))
+    }
+
+    // *********************************************************************
+    /// Create the DFA for a RegEx
+    ///
+    /// The RegEx is parsed, built into an NFA by the Thompson construction
+    /// and determinized by the subset construction. Return Err if it does
+    /// not parse.
+    pub fn from_regex(pattern: &str) -> Result<Box<DFA>, Error> {
+        DFA::from_regex_with_case(pattern, false)
+    }
+
+    /// Create the DFA for a RegEx, case insensitive if ignore_case is set
+    pub fn from_regex_with_case(pattern: &str, ignore_case: bool) -> Result<Box<DFA>, Error> {
+        let re = regex::parse_with_case(&pattern.chars().collect::<Vec<char>>(), ignore_case)?;
+        Ok(NFA::new_from_regex(&re, &re.alphabet()).subset_construction().0)
+    }
+
+    // *********************************************************************
+    /// Validate the correctness of the DFA
+    ///
+    /// States are numbered from 1, so 0 is never a valid state.
+    pub fn validate(&self) -> Result<(), Error> {
+
+        let n_states = self.transitions.len();
+        let valid = |state: usize| state >= 1 && state <= n_states;
+
+        // A character may only be in one symbol of the alphabet
+        symbol::check_disjoint(&self.alphabet)?;
+
+        // The number of characters in the alphabet should match the number
+        // of columns in each state row
+        for (rnum, row) in self.transitions.iter().enumerate() {
+            if row.len() != self.alphabet.len() {
+                return Err(Error::ColumnCount{row: rnum + 1, found: row.len(),
+                                              expected: self.alphabet.len()})
+            }
+        }
+        // Validate that all defined states in the transition table are valid
+        for (rnum, row) in self.transitions.iter().enumerate() {
+            for (cnum, state) in row.iter().enumerate() {
+                if let Some(state) = *state {
+                    if !valid(state) {
+                        return Err(Error::BadState{state,
+                                                   at: Location::Transition{row: rnum + 1, column: cnum + 1}})
+                    }
+                }
+            }
+        }
+        // The start and accept states must be valid
+        if !valid(self.start) {
+            return Err(Error::BadState{state: self.start, at: Location::Start})
+        }
+        for &acc_state in self.accept.iter() {
+            if !valid(acc_state) {
+                return Err(Error::BadState{state: acc_state, at: Location::Accept})
+            }
+        }
+        Ok(())
+    }
+
+    // *********************************************************************
+    /// Is every transition defined
+    pub fn is_complete(&self) -> bool {
+        self.transitions.iter().all(|row| row.iter().all(Option::is_some))
+    }
+
+    // *********************************************************************
+    /// Return a complete DFA accepting the same language
+    ///
+    /// If any transition is undefined a non accepting sink state, which
+    /// loops to itself on every character, is added as the last state and
+    /// the undefined transitions go to it. Otherwise the DFA is unchanged.
+    pub fn complete(&self) -> Box<DFA> {
+
+        let mut dfa = Box::new(self.clone());
+        if self.is_complete() {
+            return dfa;
+        }
+        let sink = dfa.transitions.len() + 1;
+        dfa.transitions.push(vec![None; self.alphabet.len()]);
+        for row in dfa.transitions.iter_mut() {
+            for t in row.iter_mut() {
+                t.get_or_insert(sink);
+            }
+        }
+        dfa
+    }
+
+    // *********************************************************************
+    /// Return the DFA over another alphabet
+    ///
+    /// Every symbol of the new alphabet must be inside one symbol of the
+    /// old alphabet or outside all of them, as for [`symbol::union`]. A
+    /// symbol outside them gets undefined transitions, so a string with
+    /// such a character is rejected.
+    pub fn with_alphabet(&self, alphabet: &[Symbol]) -> Box<DFA> {
+        let columns: Vec<Option<usize>> = alphabet.iter()
+                                                  .map(|s| symbol::column(&self.alphabet, s.lo))
+                                                  .collect();
+        let transitions = self.transitions.iter()
+                              .map(|row| columns.iter().map(|col| col.and_then(|c| row[c])).collect())
+                              .collect();
+        Box::new(DFA{alphabet: alphabet.to_vec(), start: self.start, accept: self.accept.clone(), transitions})
+    }
+
+    // *********************************************************************
+    /// Print DFA
+    pub fn print(&self, s: &str) {
+        println!("{}: {:?}", s, self);
+    }
+
+    // *********************************************************************
+    /// Write the DFA to stdout in the yaml format read by new_from_file
+    pub fn write_yaml(&self) {
+        print!("{}", self.to_yaml());
+    }
+
+    // *********************************************************************
+    /// Return the DFA in the yaml format read by new_from_file
+    ///
+    /// Symbols are double quoted with escapes, so control characters and
+    /// the rest of Unicode read back unchanged.
+    pub fn to_yaml(&self) -> String {
+        let alphabet: Vec<String> = self.alphabet.iter()
+                                        .map(|sym| if sym.lo == sym.hi {
+                                            format!("\"{}\"", quote(sym.lo))
+                                        } else {
+                                            format!("\"{}-{}\"", quote(sym.lo), quote(sym.hi))
+                                        })
+                                        .collect();
+        let accept: Vec<String> = self.accept.iter().map(|s| s.to_string()).collect();
+        let mut yaml = String::from("---\n");
+        yaml += &format!("alphabet: [{}]\n", alphabet.join(", "));
+        yaml += &format!("start: {}\n", self.start);
+        yaml += &format!("accept: [{}]\n", accept.join(", "));
+        yaml += "transitions:\n";
+        for row in self.transitions.iter() {
+            let row: Vec<String> = row.iter()
+                                      .map(|s| s.map_or("~".to_string(), |s| s.to_string()))
+                                      .collect();
+            yaml += &format!("  - [{}]\n", row.join(", "));
+        }
+        yaml
+    }
+}
+
+// *********************************************************************
+/// Return the character as it is written inside a double quoted yaml string
+///
+/// Printable ASCII, letters and digits are written as they are, anything
+/// else as a `\u` or `\U` escape.
+fn quote(ch: char) -> String {
+    match ch {
+        '"' | '\\' => format!("\\{}", ch),
+        ' '..='~' => ch.to_string(),
+        _ if ch.is_alphanumeric() => ch.to_string(),
+        _ if (ch as u32) <= 0xFFFF => format!("\\u{:04X}", ch as u32),
+        _ => format!("\\U{:08X}", ch as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dfa_test() {
+        let mut testdfa = Box::new(DFA::new(Vec::<char>::new(), 1, vec!(), vec!()));
+        testdfa.alphabet.push('a'.into());
+        testdfa.alphabet.push('b'.into());
+        testdfa.alphabet.push('c'.into());
+        testdfa.accept.push(2);
+        for _i in 0..5 {
+            let mut temp = Vec::new();
+            for q in 0..5 {
+                temp.push(Some(q))
+            }
+            testdfa.transitions.push(temp);
+        }
+        assert_eq!(testdfa.alphabet[1], 'b'.into());
+        assert_eq!(testdfa.accept[0], 2);
+        for temptest in testdfa.transitions.iter() {
+            for (q, &state) in temptest.iter().enumerate() {
+                assert_eq!(state, Some(q));
+            }
+        }
+    }
+
+    #[test]
+    fn validate_errors() {
+        let mut testdfa = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![2, 3]]);
+        assert!(matches!(testdfa.validate(),
+                         Err(Error::BadState{state: 3, at: Location::Transition{row: 2, column: 2}})));
+        testdfa.transitions[1] = vec![Some(2)];
+        assert!(matches!(testdfa.validate(),
+                         Err(Error::ColumnCount{row: 2, found: 1, expected: 2})));
+        testdfa.transitions[1] = vec![Some(0), Some(1)];
+        assert!(matches!(testdfa.validate(),
+                         Err(Error::BadState{state: 0, at: Location::Transition{row: 2, column: 1}})));
+        testdfa.transitions[1] = vec![Some(2), None];
+        assert!(testdfa.validate().is_ok());
+        testdfa.accept.push(5);
+        assert!(matches!(testdfa.validate(), Err(Error::BadState{state: 5, at: Location::Accept})));
+        testdfa.accept = vec![2];
+        testdfa.start = 0;
+        assert!(matches!(testdfa.validate(), Err(Error::BadState{state: 0, at: Location::Start})));
+    }
+
+    #[test]
+    fn from_yaml() {
+        let dfa: DFA = serde_yaml::from_str("
+alphabet: ['a', 'b']
+start: 1
+accept: [2]
+transitions:
+  - [2, ~]
+  - [null, 2]
+").unwrap();
+        assert!(dfa.validate().is_ok());
+        assert_eq!(dfa.transitions, vec![vec![Some(2), None], vec![None, Some(2)]]);
+        assert!(!dfa.is_complete());
+
+        // Undefined transitions go to a new sink state 3
+        let complete = dfa.complete();
+        assert!(complete.is_complete());
+        assert_eq!(*complete, DFA::new(vec!['a', 'b'], 1, vec![2],
+                                       vec![vec![2, 3], vec![3, 2], vec![3, 3]]));
+        assert_eq!(*complete.complete(), *complete);
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        // The alphabet of the RegEx x. with the quote and escape characters split out
+        let alphabet = vec![Symbol::new('\0', '!'), '"'.into(), Symbol::new('#', '['), '\\'.into(),
+                            Symbol::new(']', 'w'), 'x'.into(), Symbol::new('y', '\u{7F}'),
+                            Symbol::new('é', '中'), Symbol::new('\u{FFFE}', char::MAX), '\''.into()];
+        let rows = vec![vec![Some(1); alphabet.len()], vec![None; alphabet.len()]];
+        let dfa = DFA{alphabet, start: 1, accept: vec![2], transitions: rows};
+        let yaml = dfa.to_yaml();
+        assert!(yaml.contains(r##""\u0000-!", "\"", "#-[", "\\", "]-w", "x", "y-\u007F""##));
+        assert!(yaml.contains(r#""é-中", "\uFFFE-\U0010FFFF", "'""#));
+        assert_eq!(serde_yaml::from_str::<DFA>(&yaml).unwrap(), dfa);
+    }
+}
@@ -0,0 +1,107 @@
+//! Equivalence of DFAs
+//!
+//! Two DFAs are run side by side on the pairs of their states, with their
+//! alphabets aligned first. A breadth first walk of the pairs reachable from
+//! the start states finds the shortest string that one DFA accepts and the
+//! other rejects, or shows there is none and the languages are the same.
+
+use crate::DFA;
+use crate::symbol;
+use std::collections::HashSet;
+
+// *********************************************************************
+/// # A string on which two DFAs disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distinction {
+    /// The shortest such string, the first in alphabet order of that length
+    pub sentence: String,
+    /// Is it accepted by the first DFA (and rejected by the second)
+    pub first_accepts: bool,
+}
+
+/// A pair of states (1 relative) with the index of the pair it was reached
+/// from and the character read
+type Pair = ((usize, usize), Option<(usize, char)>);
+
+// *********************************************************************
+/// Implement equivalence checking on the DFA structure
+impl DFA {
+
+    /// Return the shortest string accepted by only one of the DFAs,
+    /// None if they accept the same language
+    ///
+    /// A character outside the alphabet of a DFA is rejected by it.
+    pub fn distinguish(&self, other: &DFA) -> Option<Distinction> {
+
+        let alphabet = symbol::union(&self.alphabet, &other.alphabet);
+        let a = self.with_alphabet(&alphabet).complete();
+        let b = other.with_alphabet(&alphabet).complete();
+
+        // Pairs of states in the order found
+        let mut pairs: Vec<Pair> = vec![((a.start, b.start), None)];
+        let mut seen = HashSet::new();
+        seen.insert((a.start, b.start));
+        let mut n = 0;
+        while n < pairs.len() {
+            let ((p, q), _) = pairs[n];
+            let first_accepts = a.accept.contains(&p);
+            if first_accepts != b.accept.contains(&q) {
+                return Some(Distinction{sentence: path(&pairs, n), first_accepts});
+            }
+            for (col, sym) in alphabet.iter().enumerate() {
+                let next = (a.transitions[p - 1][col].expect("complete DFA"),
+                            b.transitions[q - 1][col].expect("complete DFA"));
+                if seen.insert(next) {
+                    pairs.push((next, Some((n, sym.lo))));
+                }
+            }
+            n += 1;
+        }
+        None
+    }
+}
+
+// *********************************************************************
+/// Return the characters read on the way to the pair at index n
+fn path(pairs: &[Pair], mut n: usize) -> String {
+    let mut chars = Vec::new();
+    while let (_, Some((from, ch))) = pairs[n] {
+        chars.push(ch);
+        n = from;
+    }
+    chars.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::regex_dfa;
+
+    fn minimal(s: &str) -> Box<DFA> {
+        regex_dfa(s).minimize().0
+    }
+
+    fn differ(a: &str, b: &str) -> Option<(String, bool)> {
+        minimal(a).distinguish(&minimal(b)).map(|d| (d.sentence, d.first_accepts))
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(differ("(a|b)*", "(a*b*)*"), None);
+        assert_eq!(differ("a(ba)*", "(ab)*a"), None);
+        assert_eq!(differ("a*", "a+"), Some((String::new(), true)));
+        assert_eq!(differ("ab", "a[bc]"), Some(("ac".to_string(), false)));
+        assert_eq!(differ("(a|b)*abb", "(a|b)*bb"), Some(("bb".to_string(), false)));
+        assert_eq!(differ("x{2,3}", "xx|xxx"), None);
+        assert_eq!(differ("é|\\d", "[0-9é]"), None);
+    }
+
+    #[test]
+    fn regex_and_dfa() {
+        // Strings over {a, b} ending in b, with c outside the alphabet
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![1, 2]]);
+        assert_eq!(minimal("[ab]*b").distinguish(&dfa), None);
+        assert_eq!(dfa.distinguish(&minimal("[abc]*b")),
+                   Some(Distinction{sentence: "cb".to_string(), first_accepts: false}));
+    }
+}
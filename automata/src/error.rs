@@ -0,0 +1,86 @@
+//! Errors reported while reading RegExs and automata and testing strings
+
+use std::fmt;
+
+// *********************************************************************
+/// # Where an invalid state number was found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+    /// The start state
+    Start,
+    /// One of the accept states
+    Accept,
+    /// A cell of the transition matrix (1 relative row and column)
+    Transition { row: usize, column: usize },
+    /// The epsilon transitions of a state (1 relative row)
+    Epsilon { row: usize },
+}
+
+// *********************************************************************
+/// # Errors from the automata routines
+#[derive(Debug)]
+pub enum Error {
+    /// The RegEx is not well formed at the (0 relative) character position,
+    /// with a description of what would have been valid there
+    Parse { pos: usize, msg: String, expected: String },
+    /// A character that is not in the alphabet
+    UnknownSymbol(char),
+    /// A state number that does not name a state of the automaton
+    BadState { state: usize, at: Location },
+    /// A row of the transition matrix with the wrong number of columns
+    ColumnCount { row: usize, found: usize, expected: usize },
+    /// A table with the wrong number of rows
+    RowCount { found: usize, expected: usize },
+    /// Two symbols of the alphabet share a character (1 relative columns)
+    OverlappingSymbols { first: usize, second: usize },
+    /// A line of a test file that does not start with accept or reject
+    TestCase { line: usize, text: String },
+    /// The command line could not be understood
+    Usage(String),
+    /// Reading a file or the console failed
+    Io(std::io::Error),
+    /// A yaml file could not be parsed
+    Yaml(serde_yaml::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse{pos, msg, expected} => write!(f, "{} at position {}, expected {}", msg, pos, expected),
+            Error::UnknownSymbol(ch) => write!(f, "Character <{}> not in alphabet", ch),
+            Error::BadState{state, at} => match at {
+                Location::Start => write!(f, "Start state({}), is not valid", state),
+                Location::Accept => write!(f, "Accept state({}), is not valid", state),
+                Location::Transition{row, column} =>
+                    write!(f, "Invalid transition state({}) in row {}, column {}", state, row, column),
+                Location::Epsilon{row} =>
+                    write!(f, "Invalid epsilon state({}) in row {}", state, row),
+            },
+            Error::ColumnCount{row, found, expected} =>
+                write!(f, "Wrong number of columns({}) in row {}, should be {}", found, row, expected),
+            Error::RowCount{found, expected} =>
+                write!(f, "Wrong number of rows({}), should be {}", found, expected),
+            Error::OverlappingSymbols{first, second} =>
+                write!(f, "Alphabet symbols in columns {} and {} overlap", first, second),
+            Error::TestCase{line, text} =>
+                write!(f, "Line {} of the test file should start with accept or reject: {}", line, text),
+            Error::Usage(msg) => write!(f, "Usage: {}", msg),
+            Error::Io(e) => write!(f, "Unable to read input: {}", e),
+            Error::Yaml(e) => write!(f, "Unable to parse yaml: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Error {
+        Error::Yaml(e)
+    }
+}
@@ -0,0 +1,223 @@
+//! State based representation of a DFA used to test strings
+
+use crate::DFA;
+use crate::classes::{self, ClassMap};
+use crate::error::Error;
+use crate::graphviz::Digraph;
+use crate::symbol::Symbol;
+
+// *********************************************************************
+/// # Definition of a single state
+#[derive(Debug)]
+pub struct State {
+    /// Is this an accept state
+    pub accept_state: bool,
+    /// Set of transitions (0 relative), one per equivalence class
+    pub transitions: Vec<usize>
+}
+
+// *********************************************************************
+/// # State based representation of a DFA
+#[derive(Debug)]
+pub struct StateGraph {
+    /// The set of symbols comprising the alphabet
+    pub alphabet: Vec<Symbol>,
+    /// Equivalence class of each symbol of the alphabet
+    pub columns: Vec<usize>,
+    /// Equivalence class of each character of the alphabet
+    pub classes: ClassMap,
+    /// State number (0 relative) for the start state
+    pub start_state: usize,
+    /// Vector of state objects
+    pub states: Vec<State>
+}
+
+// *********************************************************************
+/// # One transition taken while tracing a string through the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// State (0 relative) the transition starts from
+    pub from: usize,
+    /// Character read
+    pub symbol: char,
+    /// State (0 relative) the transition goes to
+    pub to: usize,
+}
+
+// *********************************************************************
+/// Implement the methods of the State Graph structure
+impl StateGraph {
+
+    /// Create a state graph from a DFA structure
+    ///
+    /// A partial DFA is completed first, so its undefined transitions
+    /// go to a sink state added after the states of the DFA. Symbols that
+    /// go to the same state from every state share one column.
+    pub fn new_from_dfa(dfa: &DFA) -> Box<StateGraph> {
+
+        let dfa = dfa.complete();
+        let rows: Vec<Vec<usize>> = dfa.transitions.iter()
+            .map(|row| row.iter().map(|col| col.expect("complete DFA") - 1).collect())
+            .collect();
+        let columns = classes::group_columns(&rows, dfa.alphabet.len());
+
+        // Create an empty graph object
+        let mut graph = Box::new(StateGraph{classes: ClassMap::new(&dfa.alphabet, &columns),
+                                            alphabet: dfa.alphabet.clone(),
+                                            columns,
+                                            start_state: dfa.start - 1,
+                                            states: vec!() });
+
+        // Look through the transition table building state objects,
+        // keeping the first column of each class
+        let count = graph.columns.iter().max().map_or(0, |&c| c + 1);
+        for row in rows {
+            let mut s = State{accept_state: false, transitions: vec![0; count]};
+            for (col, to) in row.into_iter().enumerate() {
+                s.transitions[graph.columns[col]] = to;
+            }
+            graph.states.push(s);
+        }
+
+        // Set the accept states
+        for astate in dfa.accept.iter() {
+            graph.states[*astate - 1].accept_state = true;
+        }
+        graph
+    }
+
+    // *********************************************************************
+    /// Return the state (0 relative) reached from state on the symbol in
+    /// column (0 relative) of the alphabet
+    pub fn target(&self, state: usize, column: usize) -> usize {
+        self.states[state].transitions[self.columns[column]]
+    }
+
+    // *********************************************************************
+    /// Trace a string through the graph
+    ///
+    /// Return Err if a character not in the alphabet is encountered,
+    /// otherwise Ok and the transition taken for each character.
+    pub fn trace(&self, v: &[char]) -> Result<Vec<Step>, Error> {
+
+        let mut steps = Vec::new();
+        let mut state = self.start_state;
+        for &ch in v {
+            let col = match self.classes.class(ch) {
+                Some(t) => t,
+                None => return Err(Error::UnknownSymbol(ch))
+            };
+            let next = self.states[state].transitions[col];
+            steps.push(Step{from: state, symbol: ch, to: next});
+            state = next;
+        }
+        Ok(steps)
+    }
+
+    // *********************************************************************
+    /// Execute the graph on a sentence
+    /// Return Err if a character not in the alphabet is encountered
+    /// Return Ok and a bool indicating accept (true) or reject (false)
+    pub fn test_sentence(&self, sentence: &str) -> Result<bool, Error> {
+
+        let mut state = self.start_state;
+        for ch in sentence.chars() {
+            let state_no = match self.classes.class(ch) {
+                Some(t) => t,
+                None => return Err(Error::UnknownSymbol(ch))
+            };
+            state = self.states[state].transitions[state_no];
+        }
+        Ok(self.states[state].accept_state)
+    }
+
+    // *********************************************************************
+    /// Return the Graphviz definition of the graph, states are named q1, q2, ...
+    pub fn to_digraph(&self) -> Digraph {
+
+        let mut graph = Digraph::new(&format!("q{}", self.start_state+1));
+        for (n, state) in self.states.iter().enumerate() {
+            if state.accept_state {
+                graph.accept.push(format!("q{}", n+1));
+            }
+        }
+        for n in 0..self.states.len() {
+            for (i, ch) in self.alphabet.iter().enumerate() {
+                graph.edge(&format!("q{}", n+1), &format!("q{}", self.target(n, i) + 1),
+                           Some(&ch.to_string()));
+            }
+        }
+        graph
+    }
+
+    // *********************************************************************
+    /// Write the graph to stdout
+    pub fn write_graphviz(&self) {
+        print!("{}", self.to_digraph());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol;
+
+    #[test]
+    fn trace_three_symbols() {
+        // Strings over {a, b, c} containing "abc"
+        let dfa = DFA::new(vec!['a', 'b', 'c'], 1, vec![4],
+                           vec![vec![2, 1, 1], vec![2, 3, 1], vec![2, 1, 4], vec![4, 4, 4]]);
+        let graph = StateGraph::new_from_dfa(&dfa);
+        let steps = graph.trace(&['c', 'a', 'b', 'c']).unwrap();
+        assert_eq!(steps, vec![Step{from: 0, symbol: 'c', to: 0},
+                               Step{from: 0, symbol: 'a', to: 1},
+                               Step{from: 1, symbol: 'b', to: 2},
+                               Step{from: 2, symbol: 'c', to: 3}]);
+        assert!(graph.states[steps[3].to].accept_state);
+        assert!(graph.trace(&['a', 'd']).is_err());
+        assert!(graph.trace(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sentences() {
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![1, 2]]);
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert!(graph.test_sentence("aab").unwrap());
+        assert!(!graph.test_sentence("").unwrap());
+        assert!(matches!(graph.test_sentence("ac"), Err(Error::UnknownSymbol('c'))));
+        assert_eq!(graph.to_digraph().edges.len(), 4);
+        assert_eq!(graph.columns, vec![0, 1]);
+
+        // Strings of a's followed by a single b, with the rest undefined
+        let dfa = DFA{alphabet: symbol::singles(&['a', 'b']), start: 1, accept: vec![2],
+                      transitions: vec![vec![Some(1), Some(2)], vec![None, None]]};
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert_eq!(graph.states.len(), 3);
+        assert!(graph.test_sentence("aab").unwrap());
+        assert!(!graph.test_sentence("aaba").unwrap());
+        assert!(!graph.test_sentence("bb").unwrap());
+    }
+
+    #[test]
+    fn equivalent_columns() {
+        // Strings over digits and letters ending in a digit, 0-9 behave alike
+        let mut alphabet = symbol::singles(&['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']);
+        alphabet.push(Symbol::new('a', 'z'));
+        alphabet.push(Symbol::from('é'));
+        let mut rows = [vec![2; 10], vec![2; 10]];
+        rows[0].extend(&[1, 1]);
+        rows[1].extend(&[1, 1]);
+        let dfa = DFA{alphabet, start: 1, accept: vec![2],
+                      transitions: rows.iter()
+                                       .map(|row| row.iter().map(|&q| Some(q)).collect())
+                                       .collect()};
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert_eq!(graph.columns, vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert!(graph.states.iter().all(|s| s.transitions.len() == 2));
+        assert_eq!(graph.target(0, 11), 0);
+        assert!(graph.test_sentence("zé7").unwrap());
+        assert!(!graph.test_sentence("7é").unwrap());
+        assert!(matches!(graph.test_sentence("A"), Err(Error::UnknownSymbol('A'))));
+        assert_eq!(graph.to_digraph().edges.len(), 24);
+    }
+}
@@ -0,0 +1,85 @@
+//! Graphviz dot output
+//!
+//! Every program draws its automata the same way: left to right, a point
+//! named `start` with an arrow into the start state, and double circles
+//! for the accept states.
+
+use std::fmt;
+
+// *********************************************************************
+/// # A directed graph to be written in the dot language
+#[derive(Debug, Default)]
+pub struct Digraph {
+    /// Name of the node the start arrow points to
+    pub start: String,
+    /// Names of the nodes drawn with a double circle
+    pub accept: Vec<String>,
+    /// Edges in the order they are written
+    pub edges: Vec<Edge>,
+}
+
+// *********************************************************************
+/// # One edge of a Digraph
+#[derive(Debug)]
+pub struct Edge {
+    /// Name of the node the edge leaves
+    pub from: String,
+    /// Name of the node the edge enters
+    pub to: String,
+    /// Text written on the edge, if any
+    pub label: Option<String>,
+}
+
+impl Digraph {
+
+    /// Create a graph with no edges whose start arrow points to `start`
+    pub fn new(start: &str) -> Digraph {
+        Digraph{start: start.to_string(), ..Digraph::default()}
+    }
+
+    // *********************************************************************
+    /// Add an edge, with an optional label
+    pub fn edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        self.edges.push(Edge{from: from.to_string(), to: to.to_string(),
+                             label: label.map(|l| l.to_string())});
+    }
+}
+
+impl fmt::Display for Digraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "digraph {{")?;
+        writeln!(f, "\trankdir=LR;")?;
+        writeln!(f, "\tnode [shape=point]; start;")?;
+        for name in self.accept.iter() {
+            writeln!(f, "\tnode [shape=doublecircle]; {};", name)?;
+        }
+        writeln!(f, "\tnode [shape=circle];")?;
+        writeln!(f, "\tstart -> {}", self.start)?;
+        for edge in self.edges.iter() {
+            match &edge.label {
+                // Quotes and backslashes in a label must be escaped
+                Some(label) => writeln!(f, "\t{} -> {} [label=\"{}\"];", edge.from, edge.to,
+                                        label.replace('\\', "\\\\").replace('"', "\\\""))?,
+                None => writeln!(f, "\t{} -> {};", edge.from, edge.to)?,
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_dot() {
+        let mut graph = Digraph::new("q1");
+        graph.accept.push("q2".to_string());
+        graph.edge("q1", "q2", Some("\""));
+        graph.edge("q2", "q2", None);
+        assert_eq!(graph.to_string(),
+                   "digraph {\n\trankdir=LR;\n\tnode [shape=point]; start;\n\
+                    \tnode [shape=doublecircle]; q2;\n\tnode [shape=circle];\n\
+                    \tstart -> q1\n\tq1 -> q2 [label=\"\\\"\"];\n\tq2 -> q2;\n}\n");
+    }
+}
@@ -0,0 +1,67 @@
+//! Finite automata shared by the CSIS 616 programs
+//!
+//! A DFA is read from yaml with 1 relative state numbers, one row of the
+//! transition matrix per state and one column per symbol of the alphabet.
+//! A symbol is a character or a range of characters (see [`symbol`]):
+//!
+//! ```yaml
+//! alphabet: ['a', 'b']
+//! start: 1
+//! accept: [2]
+//! transitions:
+//!   - [1, 2]
+//!   - [1, 2]
+//! ```
+//!
+//! For simulation it is turned into a [`StateGraph`] with 0 relative states,
+//! where symbols that behave alike share one column (see [`classes`]).
+//! NFAs use the same layout with a set of states in each cell (see [`nfa`]),
+//! and can be built from a RegEx with [`regex::parse`] and
+//! [`NFA::new_from_regex`].
+//!
+//! Matches inside a string are found with [`StateGraph::find`] and
+//! [`StateGraph::find_iter`] (see [`search`]).
+//! The span of each capture group is found with a [`pike::Program`].
+//!
+//! Two DFAs are compared with [`DFA::distinguish`] (see [`equivalence`]) and
+//! combined into one with [`DFA::product`] (see [`product`]). The
+//! complement, reverse, concatenation and star of DFAs are in [`closure`].
+//!
+//! Whether the language of a DFA is empty or finite, and how many strings
+//! it has, is found with the graph searches in [`analysis`], and its strings
+//! are listed in shortlex order by [`StateGraph::strings`].
+//! Unreachable and dead states are found and removed with [`trim`].
+//!
+//! Strings can be checked in bulk against a file of expected verdicts
+//! with the [`batch`] module.
+
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod analysis;
+pub mod batch;
+pub mod classes;
+pub mod closure;
+pub mod dfa;
+pub mod equivalence;
+pub mod error;
+pub mod graph;
+pub mod graphviz;
+pub mod minimize;
+pub mod nfa;
+pub mod pike;
+pub mod product;
+pub mod regex;
+pub mod search;
+pub mod shortlex;
+pub mod symbol;
+#[cfg(test)]
+mod testing;
+pub mod thompson;
+pub mod trim;
+
+pub use dfa::DFA;
+pub use error::{Error, Location};
+pub use graph::{State, StateGraph, Step};
+pub use nfa::NFA;
+pub use search::Match;
+pub use symbol::Symbol;
@@ -0,0 +1,212 @@
+//! DFA minimization
+//!
+//! Hopcroft's partition refinement: start with the accept and non accept
+//! states as two blocks and keep splitting blocks whose states disagree on
+//! which block some character leads to. The blocks left at the end are the
+//! states of the minimal DFA.
+
+use crate::DFA;
+
+impl DFA {
+
+    /// Return the minimal DFA accepting the same language
+    ///
+    /// Also returns, for every state of the minimal DFA, the sorted set of
+    /// original states (1 relative) that were merged into it. States that
+    /// can not be reached from the start state are dropped and do not
+    /// appear in any set. The start state of the result is always state 1.
+    ///
+    /// A partial DFA is completed first, so the sink state it gets is the
+    /// last original state and the result is always complete.
+    pub fn minimize(&self) -> (Box<DFA>, Vec<Vec<usize>>) {
+
+        let table: Vec<Vec<usize>> = self.complete().transitions.iter()
+                                         .map(|row| row.iter().map(|t| t.expect("complete DFA")).collect())
+                                         .collect();
+        let n_states = table.len();
+        let n_symbols = self.alphabet.len();
+
+        // Only states reachable from the start state take part
+        let mut reachable = vec![false; n_states];
+        let mut stack = vec![self.start - 1];
+        reachable[self.start - 1] = true;
+        while let Some(s) = stack.pop() {
+            for &t in table[s].iter() {
+                if !reachable[t - 1] {
+                    reachable[t - 1] = true;
+                    stack.push(t - 1);
+                }
+            }
+        }
+
+        // Inverse transitions: inverse[c][q] are the states that go to q on column c
+        let mut inverse = vec![vec![vec!(); n_states]; n_symbols];
+        for (s, row) in table.iter().enumerate() {
+            if reachable[s] {
+                for (c, &t) in row.iter().enumerate() {
+                    inverse[c][t - 1].push(s);
+                }
+            }
+        }
+
+        // Initial partition: accept and non accept states
+        let mut is_accept = vec![false; n_states];
+        for &s in self.accept.iter().filter(|&&s| s >= 1 && s <= n_states) {
+            is_accept[s - 1] = true;
+        }
+        let (accepting, rejecting): (Vec<usize>, Vec<usize>) =
+            (0..n_states).filter(|&s| reachable[s])
+                         .partition(|&s| is_accept[s]);
+        let mut blocks: Vec<Vec<usize>> = vec![accepting, rejecting].into_iter()
+                                                                  .filter(|b| !b.is_empty())
+                                                                  .collect();
+        // The block of each state and its index in that block
+        let mut block_of = vec![0; n_states];
+        let mut index = vec![0; n_states];
+        for (b, block) in blocks.iter().enumerate() {
+            for (i, &s) in block.iter().enumerate() {
+                block_of[s] = b;
+                index[s] = i;
+            }
+        }
+
+        // Blocks still to be used as splitters
+        let mut work: Vec<usize> = (0..blocks.len()).collect();
+        let mut in_work = vec![true; blocks.len()];
+
+        // The states of each block leading into the splitter, only the
+        // touched blocks have any and they are emptied after each split
+        let mut marked: Vec<Vec<usize>> = Vec::new();
+        let mut touched: Vec<usize> = Vec::new();
+
+        while let Some(splitter) = work.pop() {
+            in_work[splitter] = false;
+            let splitter_states = blocks[splitter].clone();
+
+            for col_inverse in inverse.iter() {
+                // Group the states leading into the splitter by their block
+                marked.resize_with(blocks.len(), Vec::new);
+                for &q in splitter_states.iter() {
+                    for &p in col_inverse[q].iter() {
+                        let b = block_of[p];
+                        if marked[b].is_empty() {
+                            touched.push(b);
+                        }
+                        marked[b].push(p);
+                    }
+                }
+
+                // Split every block that only partly leads into the splitter
+                for b in touched.drain(..) {
+                    if marked[b].len() == blocks[b].len() {
+                        marked[b].clear();
+                        continue;
+                    }
+                    let moved: Vec<usize> = marked[b].drain(..).collect();
+                    let new_block = blocks.len();
+                    for (i, &s) in moved.iter().enumerate() {
+                        // Fill the hole left in the old block with its last state
+                        let last = blocks[b].pop().expect("state in block");
+                        if last != s {
+                            blocks[b][index[s]] = last;
+                            index[last] = index[s];
+                        }
+                        block_of[s] = new_block;
+                        index[s] = i;
+                    }
+                    blocks.push(moved);
+
+                    if in_work[b] || blocks[new_block].len() <= blocks[b].len() {
+                        work.push(new_block);
+                        in_work.push(true);
+                    } else {
+                        work.push(b);
+                        in_work[b] = true;
+                        in_work.push(false);
+                    }
+                }
+            }
+        }
+
+        // Number the blocks in the order they are first reached from the start
+        let mut number = vec![0; blocks.len()];
+        let mut order = vec![block_of[self.start - 1]];
+        number[order[0]] = 1;
+        let mut next = 0;
+        while next < order.len() {
+            let rep = blocks[order[next]][0];
+            for &t in table[rep].iter() {
+                let b = block_of[t - 1];
+                if number[b] == 0 {
+                    order.push(b);
+                    number[b] = order.len();
+                }
+            }
+            next += 1;
+        }
+
+        let mut dfa = Box::new(DFA{alphabet: self.alphabet.clone(),
+                                   start: 1, accept: vec!(),
+                                   transitions: vec!()});
+        let mut merged = Vec::new();
+        for (n, &b) in order.iter().enumerate() {
+            let rep = blocks[b][0];
+            dfa.transitions.push(table[rep].iter()
+                                           .map(|&t| Some(number[block_of[t - 1]]))
+                                           .collect());
+            if is_accept[rep] {
+                dfa.accept.push(n + 1);
+            }
+            let mut states: Vec<usize> = blocks[b].iter().map(|s| s + 1).collect();
+            states.sort_unstable();
+            merged.push(states);
+        }
+        (dfa, merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_equivalent_states() {
+        // Strings over {a, b} ending in b, with states 2 and 3 equivalent
+        // and state 4 unreachable
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec![2, 3],
+                           vec![vec![1, 2], vec![1, 3], vec![1, 2], vec![4, 4]]);
+        let (min, merged) = dfa.minimize();
+        assert!(min.validate().is_ok());
+        assert_eq!(*min, DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![1, 2]]));
+        assert_eq!(merged, vec![vec![1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn already_minimal() {
+        // Strings over {a} whose length is a multiple of 3
+        let dfa = DFA::new(vec!['a'], 2, vec![2], vec![vec![2], vec![3], vec![1]]);
+        let (min, merged) = dfa.minimize();
+        assert_eq!(*min, DFA::new(vec!['a'], 1, vec![1], vec![vec![2], vec![3], vec![1]]));
+        assert_eq!(merged, vec![vec![2], vec![3], vec![1]]);
+    }
+
+    #[test]
+    fn single_block() {
+        // Every state rejects, so everything collapses into one state
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec!(), vec![vec![2, 3], vec![3, 1], vec![1, 2]]);
+        let (min, merged) = dfa.minimize();
+        assert_eq!(*min, DFA::new(vec!['a', 'b'], 1, vec!(), vec![vec![1, 1]]));
+        assert_eq!(merged, vec![vec![1, 2, 3]]);
+    }
+
+    #[test]
+    fn partial_dfa() {
+        // a*b with undefined transitions, the sink added as state 3 is kept
+        let dfa = DFA{alphabet: crate::symbol::singles(&['a', 'b']), start: 1, accept: vec![2],
+                      transitions: vec![vec![Some(1), Some(2)], vec![None, None]]};
+        let (min, merged) = dfa.minimize();
+        assert_eq!(*min, DFA::new(vec!['a', 'b'], 1, vec![2],
+                                  vec![vec![1, 2], vec![3, 3], vec![3, 3]]));
+        assert_eq!(merged, vec![vec![1], vec![2], vec![3]]);
+    }
+}
@@ -0,0 +1,493 @@
+//! Nondeterministic finite automata
+//!
+//! An NFA is laid out like the DFA structure, except that every cell
+//! of the transition matrix holds a set of states and every state has
+//! an additional set of epsilon transitions.
+//!
+//! In yaml each transition cell is a list of states and the epsilon
+//! transitions are a separate list with one row per state, which may be
+//! left out when there are none:
+//!
+//! ```yaml
+//! alphabet: ['a', 'b']
+//! start: 1
+//! accept: [3]
+//! transitions:
+//!   - [[1], [1, 2]]
+//!   - [[], []]
+//!   - [[], []]
+//! epsilon:
+//!   - []
+//!   - [3]
+//!   - []
+//! ```
+//!
+//! An NFA built from a RegEx may also have assertion edges for `^`, `$`,
+//! `\b` and `\B`, which are epsilon transitions that are only taken where
+//! their assertion holds. The alphabet of such an NFA must not have a
+//! symbol with both word and non word characters.
+
+use crate::DFA;
+use crate::error::{Error, Location};
+use crate::graphviz::Digraph;
+use crate::regex::{self, Assertion};
+use crate::search::{Match, Matches};
+use crate::symbol::{self, Symbol};
+use serde::{Deserialize};
+use std::collections::HashMap;
+
+// *********************************************************************
+/// # Definition of an NFA
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NFA {
+    /// The set of symbols comprising the alphabet
+    pub alphabet: Vec<Symbol>,
+    /// State number (1 relative) for the start state
+    pub start: usize,
+    /// Set of accept states (1 relative)
+    pub accept: Vec<usize>,
+    /// Matrix of transitions, rows are states, columns characters in the alphabet,
+    /// each cell is the set of states (1 relative) reached on that character
+    pub transitions: Vec<Vec<Vec<usize>>>,
+    /// Epsilon transitions, one set of states (1 relative) per state
+    #[serde(default)]
+    pub epsilon: Vec<Vec<usize>>,
+    /// Assertion edges as (from, assertion, to) with 1 relative states
+    #[serde(skip)]
+    pub assertions: Vec<(usize, Assertion, usize)>,
+}
+
+/// Whether the character before or after a position is a word character,
+/// None at the start or end of the string
+type Side = Option<bool>;
+
+// *********************************************************************
+/// Implement the methods of the NFA structure
+impl NFA {
+
+    /// Create and return an NFA on the heap
+    ///
+    /// Load the .yaml file specified into an NFA structure
+    /// on the heap and return a point to it via a Box.
+    pub fn new_from_file(filename: &str) -> Result<Box<NFA>, Error> {
+
+        let f = std::fs::File::open(filename)?;
+
+        // Deserialize into the heap, a missing epsilon entry means
+        // there are no epsilon transitions
+        let mut nfa: Box<NFA> = Box::new(serde_yaml::from_reader(f)?);
+        if nfa.epsilon.is_empty() {
+            nfa.epsilon = vec![vec!(); nfa.transitions.len()];
+        }
+        Ok(nfa)
+    }
+
+    // *********************************************************************
+    /// Validate the correctness of the NFA
+    pub fn validate(&self) -> Result<(), Error> {
+
+        let n_states = self.transitions.len();
+        let valid = |state: usize| state >= 1 && state <= n_states;
+
+        // A character may only be in one symbol of the alphabet
+        symbol::check_disjoint(&self.alphabet)?;
+
+        // The number of characters in the alphabet should match the number
+        // of columns in each state row
+        for (rnum, row) in self.transitions.iter().enumerate() {
+            if row.len() != self.alphabet.len() {
+                return Err(Error::ColumnCount{row: rnum + 1, found: row.len(),
+                                              expected: self.alphabet.len()})
+            }
+        }
+        if self.epsilon.len() != n_states {
+            return Err(Error::RowCount{found: self.epsilon.len(), expected: n_states})
+        }
+
+        // Validate that all states in the transition table are valid
+        for (rnum, row) in self.transitions.iter().enumerate() {
+            for (cnum, cell) in row.iter().enumerate() {
+                if let Some(&state) = cell.iter().find(|&&s| !valid(s)) {
+                    return Err(Error::BadState{state,
+                                               at: Location::Transition{row: rnum + 1, column: cnum + 1}})
+                }
+            }
+        }
+        for (rnum, row) in self.epsilon.iter().enumerate() {
+            if let Some(&state) = row.iter().find(|&&s| !valid(s)) {
+                return Err(Error::BadState{state, at: Location::Epsilon{row: rnum + 1}})
+            }
+        }
+        for &(from, _, to) in self.assertions.iter() {
+            if !valid(from) || !valid(to) {
+                return Err(Error::BadState{state: if valid(from) { to } else { from },
+                                           at: Location::Epsilon{row: from}})
+            }
+        }
+
+        // The start and accept states must be valid
+        if !valid(self.start) {
+            return Err(Error::BadState{state: self.start, at: Location::Start})
+        }
+        for &acc_state in self.accept.iter() {
+            if !valid(acc_state) {
+                return Err(Error::BadState{state: acc_state, at: Location::Accept})
+            }
+        }
+        Ok(())
+    }
+
+    // *********************************************************************
+    /// Return the sorted set of states reachable from `states` using
+    /// only epsilon transitions (including the states themselves)
+    pub fn epsilon_closure(&self, states: &[usize]) -> Vec<usize> {
+        Closer::new(self).close(states, |_| false)
+    }
+
+    // *********************************************************************
+    /// Return the sorted set of states reachable from `states` using epsilon
+    /// transitions and the assertion edges that hold at a position between
+    /// the sides given
+    pub fn closure_at(&self, states: &[usize], before: Side, after: Side) -> Vec<usize> {
+        Closer::new(self).close_at(states, before, after)
+    }
+
+    // *********************************************************************
+    /// Return the set of states reached from `states` on alphabet column `col`,
+    /// not including epsilon transitions
+    pub fn step(&self, states: &[usize], col: usize) -> Vec<usize> {
+        let mut next: Vec<usize> = states.iter()
+                                         .flat_map(|&s| self.transitions[s - 1][col].iter().copied())
+                                         .collect();
+        next.sort_unstable();
+        next.dedup();
+        next
+    }
+
+    // *********************************************************************
+    /// Is any state of the set an accept state
+    pub fn is_accepting(&self, states: &[usize]) -> bool {
+        states.iter().any(|s| self.accept.contains(s))
+    }
+
+    // *********************************************************************
+    /// Execute the NFA on a sentence by tracking the set of current states
+    /// Return Err if a character not in the alphabet is encountered
+    /// Return Ok and a bool indicating accept (true) or reject (false)
+    pub fn test_sentence(&self, sentence: &str) -> Result<bool, Error> {
+
+        // States are closed just before reading the next character, when
+        // the characters on both sides are known
+        let mut closer = Closer::new(self);
+        let mut current = vec![self.start];
+        let mut before = None;
+        for ch in sentence.chars() {
+            let col = match symbol::column(&self.alphabet, ch) {
+                Some(t) => t,
+                None => return Err(Error::UnknownSymbol(ch))
+            };
+            let after = Some(regex::is_word(ch));
+            current = self.step(&closer.close_at(&current, before, after), col);
+            before = after;
+        }
+        Ok(self.is_accepting(&closer.close_at(&current, before, None)))
+    }
+
+    // *********************************************************************
+    /// Return the leftmost-longest match starting at or after byte offset from
+    ///
+    /// Unlike [`StateGraph::find`](crate::StateGraph::find) the assertions
+    /// see the characters around the match.
+    pub fn find(&self, text: &str, from: usize) -> Option<Match> {
+
+        let side = |ch: Option<char>| ch.map(regex::is_word);
+        let mut closer = Closer::new(self);
+        let starts = text[from..].char_indices().map(|(i, _)| from + i)
+                                 .chain(std::iter::once(text.len()));
+        for start in starts {
+            let mut before = side(text[..start].chars().next_back());
+            let mut current = vec![self.start];
+            let mut end = None;
+            let mut chars = text[start..].char_indices().peekable();
+            loop {
+                let next = chars.peek().map(|&(_, ch)| ch);
+                let after = side(next);
+                let closed = closer.close_at(&current, before, after);
+                let pos = chars.peek().map_or(text.len(), |&(i, _)| start + i);
+                if self.is_accepting(&closed) {
+                    end = Some(pos);
+                }
+                let col = match next.and_then(|ch| symbol::column(&self.alphabet, ch)) {
+                    Some(col) => col,
+                    None => break,
+                };
+                current = self.step(&closed, col);
+                if current.is_empty() {
+                    break;
+                }
+                chars.next();
+                before = after;
+            }
+            if let Some(end) = end {
+                return Some(Match{start, end});
+            }
+        }
+        None
+    }
+
+    // *********************************************************************
+    /// Return an iterator over the leftmost-longest non-overlapping matches
+    pub fn find_iter<'n, 't>(&'n self, text: &'t str)
+                             -> Matches<'t, impl Fn(&str, usize) -> Option<Match> + 'n> {
+        Matches::new(text, move |text: &str, from| self.find(text, from))
+    }
+
+    // *********************************************************************
+    /// Subset (powerset) construction of a DFA accepting the same language
+    ///
+    /// Also returns, for every DFA state, the sorted set of NFA states it
+    /// stands for. The DFA state numbered n (1 relative) is the set at index
+    /// n-1. The empty set, if reachable, becomes a dead state.
+    ///
+    /// With assertion edges a DFA state also remembers whether the last
+    /// character read was a word character, and its set is closed only
+    /// when the next character is known. Its set of NFA states is then the
+    /// one before that closure.
+    pub fn subset_construction(&self) -> (Box<DFA>, Vec<Vec<usize>>) {
+
+        let mut dfa = Box::new(DFA{alphabet: self.alphabet.clone(),
+                                   start: 1, accept: vec!(),
+                                   transitions: vec!()});
+        let plain = self.assertions.is_empty();
+        let mut closer = Closer::new(self);
+        let first = if plain { closer.close(&[self.start], |_| false) } else { vec![self.start] };
+        let mut states: Vec<(Vec<usize>, Side)> = vec![(first, None)];
+        let mut numbers = HashMap::new();
+        numbers.insert(states[0].clone(), 1);
+
+        // Each set is processed once, new sets found along the way are
+        // appended and get the next state number
+        let mut n = 0;
+        while n < states.len() {
+            let (set, before) = states[n].clone();
+            let mut row = Vec::new();
+            for col in 0..self.alphabet.len() {
+                let next = if plain {
+                    (closer.close(&self.step(&set, col), |_| false), None)
+                } else {
+                    let after = Some(regex::is_word(self.alphabet[col].lo));
+                    (self.step(&closer.close_at(&set, before, after), col), after)
+                };
+                let state = match numbers.get(&next) {
+                    Some(&t) => t,
+                    None => {
+                        states.push(next.clone());
+                        numbers.insert(next, states.len());
+                        states.len()
+                    }
+                };
+                row.push(Some(state));
+            }
+            let accepting = if plain { self.is_accepting(&set) }
+                            else { self.is_accepting(&closer.close_at(&set, before, None)) };
+            if accepting {
+                dfa.accept.push(n + 1);
+            }
+            dfa.transitions.push(row);
+            n += 1;
+        }
+        (dfa, states.into_iter().map(|(set, _)| set).collect())
+    }
+
+    // *********************************************************************
+    /// Return the Graphviz definition of the NFA, epsilon transitions are labeled with ε
+    pub fn to_digraph(&self) -> Digraph {
+
+        let mut graph = Digraph::new(&format!("q{}", self.start));
+        graph.accept = self.accept.iter().map(|s| format!("q{}", s)).collect();
+
+        for (n, row) in self.transitions.iter().enumerate() {
+            for (i, ch) in self.alphabet.iter().enumerate() {
+                for to in row[i].iter() {
+                    graph.edge(&format!("q{}", n+1), &format!("q{}", to), Some(&ch.to_string()));
+                }
+            }
+            for to in self.epsilon[n].iter() {
+                graph.edge(&format!("q{}", n+1), &format!("q{}", to), Some("\u{03B5}"));
+            }
+        }
+        for (from, assertion, to) in self.assertions.iter() {
+            graph.edge(&format!("q{}", from), &format!("q{}", to), Some(&assertion.to_string()));
+        }
+        graph
+    }
+
+    // *********************************************************************
+    /// Write the NFA to stdout
+    pub fn write_graphviz(&self) {
+        print!("{}", self.to_digraph());
+    }
+}
+
+// *********************************************************************
+/// Computes closures of sets of NFA states
+///
+/// The assertion edges are indexed by the state they leave and the
+/// buffer of visited states is reused, so a closure costs only as much
+/// as the states and edges it visits. The subset construction takes
+/// one closure for every DFA state and character.
+struct Closer<'a> {
+    nfa: &'a NFA,
+    /// Assertion edges leaving each state (0 relative) as (assertion, to)
+    assertions: Vec<Vec<(Assertion, usize)>>,
+    /// Visited states, all false between closures
+    seen: Vec<bool>,
+}
+
+impl<'a> Closer<'a> {
+    fn new(nfa: &'a NFA) -> Closer<'a> {
+        let mut assertions = vec![vec!(); nfa.epsilon.len()];
+        for &(from, assertion, to) in nfa.assertions.iter() {
+            assertions[from - 1].push((assertion, to));
+        }
+        Closer{nfa, assertions, seen: vec![false; nfa.epsilon.len()]}
+    }
+
+    /// Closure taking the assertion edges that hold between the sides given
+    fn close_at(&mut self, states: &[usize], before: Side, after: Side) -> Vec<usize> {
+        self.close(states, |assertion| assertion.holds(before, after))
+    }
+
+    /// Closure taking the assertion edges for which holds is true
+    fn close<F>(&mut self, states: &[usize], holds: F) -> Vec<usize> where F: Fn(Assertion) -> bool {
+        let mut closed = Vec::new();
+        let mut stack = states.to_vec();
+        while let Some(s) = stack.pop() {
+            if self.seen[s - 1] {
+                continue;
+            }
+            self.seen[s - 1] = true;
+            closed.push(s);
+            let seen = &self.seen;
+            stack.extend(self.nfa.epsilon[s - 1].iter().filter(|&&t| !seen[t - 1]));
+            stack.extend(self.assertions[s - 1].iter()
+                             .filter(|&&(assertion, to)| !seen[to - 1] && holds(assertion))
+                             .map(|&(_, to)| to));
+        }
+        for &s in closed.iter() {
+            self.seen[s - 1] = false;
+        }
+        closed.sort_unstable();
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walk the DFA transition table on a sentence
+    fn dfa_accepts(dfa: &DFA, sentence: &str) -> bool {
+        let mut state = dfa.start;
+        for ch in sentence.chars() {
+            let col = symbol::column(&dfa.alphabet, ch).unwrap();
+            state = dfa.transitions[state - 1][col].unwrap();
+        }
+        dfa.accept.contains(&state)
+    }
+
+    #[test]
+    fn closure_and_step() {
+        // 1 -ε-> 2 -a-> 3 -ε-> 1
+        let nfa = NFA{alphabet: symbol::singles(&['a']), start: 1, accept: vec![3],
+                      transitions: vec![vec![vec!()], vec![vec![3]], vec![vec!()]],
+                      epsilon: vec![vec![2], vec!(), vec![1]], assertions: vec!()};
+        assert_eq!(nfa.epsilon_closure(&[1]), vec![1, 2]);
+        assert_eq!(nfa.epsilon_closure(&[3]), vec![1, 2, 3]);
+        assert_eq!(nfa.step(&[1, 2], 0), vec![3]);
+        assert!(nfa.step(&[1], 0).is_empty());
+        assert!(nfa.is_accepting(&[1, 3]));
+        assert!(!nfa.is_accepting(&[1, 2]));
+    }
+
+    #[test]
+    fn from_yaml() {
+        // Strings over {a, b} whose second to last character is a b
+        let nfa: NFA = serde_yaml::from_str("
+alphabet: ['a', 'b']
+start: 1
+accept: [4]
+transitions:
+  - [[1], [1, 2]]
+  - [[], []]
+  - [[4], [4]]
+  - [[], []]
+epsilon:
+  - []
+  - [3]
+  - []
+  - []
+").unwrap();
+        assert!(nfa.validate().is_ok());
+        assert!(nfa.test_sentence("ba").unwrap());
+        assert!(!nfa.test_sentence("abab").unwrap());
+        assert!(nfa.test_sentence("aabb").unwrap());
+        assert!(matches!(nfa.test_sentence("c"), Err(Error::UnknownSymbol('c'))));
+
+        let (dfa, _) = nfa.subset_construction();
+        for s in ["", "b", "ba", "bb", "abab", "aabb", "bbaa"].iter() {
+            assert_eq!(dfa_accepts(&dfa, s), nfa.test_sentence(s).unwrap(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn invalid_states() {
+        let mut nfa = NFA{alphabet: symbol::singles(&['a']), start: 1, accept: vec!(),
+                          transitions: vec![vec![vec![2]]], epsilon: vec![vec!()],
+                          assertions: vec!()};
+        assert!(matches!(nfa.validate(),
+                         Err(Error::BadState{state: 2, at: Location::Transition{row: 1, column: 1}})));
+        nfa.transitions[0][0] = vec![0];
+        assert!(nfa.validate().is_err());
+        nfa.transitions[0][0] = vec![1];
+        assert!(nfa.validate().is_ok());
+        nfa.epsilon.push(vec!());
+        assert!(matches!(nfa.validate(), Err(Error::RowCount{found: 2, expected: 1})));
+    }
+
+    #[test]
+    fn subset_construction() {
+        // (a|b)*b over {a, b}: 1 -ε-> 2, 2 -a,b-> 2, 2 -b-> 3
+        let nfa = NFA{alphabet: symbol::singles(&['a', 'b']), start: 1, accept: vec![3],
+                      transitions: vec![vec![vec!(), vec!()],
+                                        vec![vec![2], vec![2, 3]],
+                                        vec![vec!(), vec!()]],
+                      epsilon: vec![vec![2], vec!(), vec!()], assertions: vec!()};
+
+        let (dfa, sets) = nfa.subset_construction();
+        assert!(dfa.validate().is_ok());
+        assert_eq!(sets, vec![vec![1, 2], vec![2], vec![2, 3]]);
+        assert_eq!(dfa.start, 1);
+        assert_eq!(dfa.accept, vec![3]);
+        assert_eq!(*dfa, DFA::new(vec!['a', 'b'], 1, vec![3],
+                                  vec![vec![2, 3], vec![2, 3], vec![2, 3]]));
+
+        for s in ["", "a", "ba", "aba"].iter() {
+            assert!(!dfa_accepts(&dfa, s));
+        }
+        assert!(dfa_accepts(&dfa, "aab"));
+    }
+
+    #[test]
+    fn assertions() {
+        let re = regex::parse(&"\\bcat\\b|^do".chars().collect::<Vec<char>>()).unwrap();
+        let nfa = NFA::new_from_regex(&re, &re.alphabet());
+        assert!(nfa.validate().is_ok());
+        assert!(nfa.test_sentence("cat").unwrap());
+        assert!(nfa.test_sentence("do").unwrap());
+        let spans: Vec<(usize, usize)> = nfa.find_iter("do concat cat_ cat, do")
+                                            .map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, vec![(0, 2), (15, 18)]);
+        assert_eq!(nfa.find("xcat", 0), None);
+    }
+}
@@ -0,0 +1,284 @@
+//! Capture groups with a Pike VM
+//!
+//! A DFA only tells whether a string matches. To find the part of the
+//! string each group matched, the RegEx is compiled into a small program
+//! that is run on all its threads at once, one character at a time. Each
+//! thread carries the offsets where the groups it went through started
+//! and ended. Threads are kept in priority order, so alternatives are
+//! tried from left to right and repetitions match as much as they can.
+
+use crate::regex::{self, Assertion, Class, Regex};
+use crate::search::Match;
+
+// *********************************************************************
+/// One instruction of the program
+#[derive(Debug, Clone)]
+enum Inst {
+    /// Read the character
+    Char(char),
+    /// Read a character of the class
+    Class(Class),
+    /// Continue at both, the first has priority
+    Split(usize, usize),
+    /// Continue at the instruction
+    Jump(usize),
+    /// Record the current offset in the slot
+    Save(usize),
+    /// Continue only if the assertion holds at the current offset
+    Assert(Assertion),
+    /// The whole RegEx matched
+    Match,
+}
+
+// *********************************************************************
+/// # Compiled RegEx that reports the span of each capture group
+#[derive(Debug, Clone)]
+pub struct Program {
+    insts: Vec<Inst>,
+    /// Number of capture groups, not counting group 0 for the whole match
+    groups: usize,
+}
+
+/// Offsets recorded by a thread, the start and end of each group
+type Slots = Vec<Option<usize>>;
+
+impl Program {
+
+    /// Compile the RegEx
+    pub fn new(re: &Regex) -> Program {
+        let mut program = Program{insts: Vec::new(), groups: group_count(re)};
+        program.insts.push(Inst::Save(0));
+        program.add_regex(re);
+        program.insts.push(Inst::Save(1));
+        program.insts.push(Inst::Match);
+        program
+    }
+
+    // *********************************************************************
+    /// Return the number of capture groups, not counting the whole match
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    // *********************************************************************
+    /// Match the whole string
+    ///
+    /// Return None if it does not match, otherwise the span of the whole
+    /// string followed by the span of each group, None for a group that
+    /// took no part in the match. A group that matched more than once
+    /// gives its last match.
+    pub fn captures(&self, text: &str) -> Option<Vec<Option<Match>>> {
+        self.captures_in(text, Match{start: 0, end: text.len()})
+    }
+
+    // *********************************************************************
+    /// Match the whole span of the string, as [`captures`](Program::captures)
+    ///
+    /// The assertions see the characters around the span, so a match found
+    /// by a search gets the same groups as it would in the whole string.
+    pub fn captures_in(&self, text: &str, span: Match) -> Option<Vec<Option<Match>>> {
+
+        let mut threads: Vec<(usize, Slots)> = Vec::new();
+        let mut seen = vec![false; self.insts.len()];
+        self.add_thread(text, &mut threads, &mut seen, 0, vec![None; 2 * self.groups + 2], span.start);
+
+        let chars = text[span.start..span.end].char_indices()
+                                              .map(|(i, ch)| Some((span.start + i, ch)))
+                                              .chain(std::iter::once(None));
+        for next in chars {
+            let mut following = Vec::new();
+            let mut seen = vec![false; self.insts.len()];
+            for (pc, slots) in threads {
+                match (&self.insts[pc], next) {
+                    (Inst::Match, None) => return Some(spans(&slots)),
+                    (Inst::Char(c), Some((i, ch))) if *c == ch => {
+                        self.add_thread(text, &mut following, &mut seen, pc + 1, slots, i + ch.len_utf8());
+                    }
+                    (Inst::Class(class), Some((i, ch))) if class.matches(ch) => {
+                        self.add_thread(text, &mut following, &mut seen, pc + 1, slots, i + ch.len_utf8());
+                    }
+                    _ => {}
+                }
+            }
+            threads = following;
+        }
+        None
+    }
+
+    // *********************************************************************
+    /// Add the thread at pc and the threads it leads to without reading,
+    /// in priority order, to the list
+    ///
+    /// An instruction already seen at this offset was reached by a thread
+    /// of higher priority, so it is not followed again.
+    fn add_thread(&self, text: &str, list: &mut Vec<(usize, Slots)>, seen: &mut [bool],
+                  pc: usize, slots: Slots, pos: usize) {
+
+        let before = text[..pos].chars().next_back().map(regex::is_word);
+        let after = text[pos..].chars().next().map(regex::is_word);
+
+        // Stack of threads still to follow, the top has the highest priority
+        let mut stack = vec![(pc, slots)];
+        while let Some((pc, mut slots)) = stack.pop() {
+            if seen[pc] {
+                continue;
+            }
+            seen[pc] = true;
+            match &self.insts[pc] {
+                Inst::Jump(to) => stack.push((*to, slots)),
+                Inst::Split(first, second) => {
+                    stack.push((*second, slots.clone()));
+                    stack.push((*first, slots));
+                }
+                Inst::Save(slot) => {
+                    slots[*slot] = Some(pos);
+                    stack.push((pc + 1, slots));
+                }
+                Inst::Assert(assertion) => {
+                    if assertion.holds(before, after) {
+                        stack.push((pc + 1, slots));
+                    }
+                }
+                _ => list.push((pc, slots)),
+            }
+        }
+    }
+
+    // *********************************************************************
+    /// Add the instructions for a sub expression
+    fn add_regex(&mut self, re: &Regex) {
+        match re {
+            Regex::Empty => {}
+            Regex::Literal(ch) => self.insts.push(Inst::Char(*ch)),
+            Regex::Class(class) => self.insts.push(Inst::Class(class.clone())),
+            Regex::Assert(assertion) => self.insts.push(Inst::Assert(*assertion)),
+            Regex::Concat(items) => items.iter().for_each(|item| self.add_regex(item)),
+            Regex::Alt(branches) => {
+                // Each branch but the last is tried first, then the rest
+                let mut jumps = Vec::new();
+                for branch in &branches[..branches.len() - 1] {
+                    let split = self.add_inst(Inst::Split(0, 0));
+                    self.add_regex(branch);
+                    jumps.push(self.add_inst(Inst::Jump(0)));
+                    self.insts[split] = Inst::Split(split + 1, self.insts.len());
+                }
+                self.add_regex(&branches[branches.len() - 1]);
+                for jump in jumps {
+                    self.insts[jump] = Inst::Jump(self.insts.len());
+                }
+            }
+            Regex::Star(inner) => self.add_star(inner),
+            Regex::Plus(inner) => {
+                let start = self.insts.len();
+                self.add_regex(inner);
+                self.add_inst(Inst::Split(start, self.insts.len() + 1));
+            }
+            Regex::Optional(inner) => self.add_optional(inner),
+            Regex::Repeat{inner, min, max} => {
+                for _ in 0..*min {
+                    self.add_regex(inner);
+                }
+                match max {
+                    None => self.add_star(inner),
+                    Some(max) => (*min..*max).for_each(|_| self.add_optional(inner)),
+                }
+            }
+            Regex::Group{index, inner} => {
+                self.add_inst(Inst::Save(2 * index));
+                self.add_regex(inner);
+                self.add_inst(Inst::Save(2 * index + 1));
+            }
+        }
+    }
+
+    /// Add zero or more repetitions of the sub expression
+    fn add_star(&mut self, inner: &Regex) {
+        let split = self.add_inst(Inst::Split(0, 0));
+        self.add_regex(inner);
+        self.add_inst(Inst::Jump(split));
+        self.insts[split] = Inst::Split(split + 1, self.insts.len());
+    }
+
+    /// Add the sub expression or nothing
+    fn add_optional(&mut self, inner: &Regex) {
+        let split = self.add_inst(Inst::Split(0, 0));
+        self.add_regex(inner);
+        self.insts[split] = Inst::Split(split + 1, self.insts.len());
+    }
+
+    /// Add an instruction and return its index
+    fn add_inst(&mut self, inst: Inst) -> usize {
+        self.insts.push(inst);
+        self.insts.len() - 1
+    }
+}
+
+// *********************************************************************
+/// Return the largest group index in the RegEx, even for a group repeated 0 times
+fn group_count(re: &Regex) -> usize {
+    match re {
+        Regex::Empty | Regex::Literal(_) | Regex::Class(_) | Regex::Assert(_) => 0,
+        Regex::Concat(items) | Regex::Alt(items) => items.iter().map(group_count).max().unwrap_or(0),
+        Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) |
+        Regex::Repeat{inner, ..} => group_count(inner),
+        Regex::Group{index, inner} => group_count(inner).max(*index),
+    }
+}
+
+// *********************************************************************
+/// Turn the slots of a thread into the span of each group
+fn spans(slots: &[Option<usize>]) -> Vec<Option<Match>> {
+    slots.chunks(2)
+         .map(|pair| match (pair[0], pair[1]) {
+             (Some(start), Some(end)) => Some(Match{start, end}),
+             _ => None,
+         })
+         .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regex;
+
+    fn program(s: &str) -> Program {
+        Program::new(&regex::parse(&s.chars().collect::<Vec<char>>()).unwrap())
+    }
+
+    fn groups<'t>(s: &str, text: &'t str) -> Option<Vec<Option<&'t str>>> {
+        program(s).captures(text)
+                  .map(|spans| spans.iter().map(|m| m.map(|m| m.as_str(text))).collect())
+    }
+
+    #[test]
+    fn submatches() {
+        assert_eq!(groups("(a+)(b*)", "aabb"), Some(vec![Some("aabb"), Some("aa"), Some("bb")]));
+        assert_eq!(groups("(a|ab)(c|bcd)", "abcd"), Some(vec![Some("abcd"), Some("a"), Some("bcd")]));
+        assert_eq!(groups("(a*)*", "b"), None);
+        assert_eq!(groups("(a)|b", "b"), Some(vec![Some("b"), None]));
+        assert_eq!(groups("(?:(a)|b)*", "ab"), Some(vec![Some("ab"), Some("a")]));
+        assert_eq!(groups("(\\w+)@(\\w+)\\.com", "me@here.com"),
+                   Some(vec![Some("me@here.com"), Some("me"), Some("here")]));
+        assert_eq!(program("(a)(?:b)(c)").groups(), 2);
+        assert_eq!(groups("(b)(a){0}", "b"), Some(vec![Some("b"), Some("b"), None]));
+    }
+
+    #[test]
+    fn greedy_repeats() {
+        assert_eq!(groups("(a*)(a*)", "aaa"), Some(vec![Some("aaa"), Some("aaa"), Some("")]));
+        assert_eq!(groups("(a{1,2})(a?)(a*)", "aaaa"),
+                   Some(vec![Some("aaaa"), Some("aa"), Some("a"), Some("a")]));
+        assert_eq!(groups("(é|中)+", "é中"), Some(vec![Some("é中"), Some("中")]));
+        assert_eq!(groups("()", ""), Some(vec![Some(""), Some("")]));
+    }
+
+    #[test]
+    fn assertions() {
+        assert_eq!(groups("^(a*)\\b(\\w*)$", "aab"), Some(vec![Some("aab"), Some(""), Some("aab")]));
+        assert_eq!(groups("(a*)\\B(\\w*)", "aab"), Some(vec![Some("aab"), Some("aa"), Some("b")]));
+        let text = "x ab";
+        let span = Match{start: 2, end: 4};
+        assert_eq!(program("\\b(a)b").captures_in(text, span).unwrap()[1], Some(Match{start: 2, end: 3}));
+        assert_eq!(program("^ab").captures_in(text, span), None);
+    }
+}
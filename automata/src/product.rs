@@ -0,0 +1,134 @@
+//! Product construction of DFAs
+//!
+//! Two DFAs are run side by side: each state of the product is a pair of
+//! states, one from each DFA, and reading a character moves both. Whether a
+//! pair accepts depends on the [`Operation`], so the same product gives the
+//! union, intersection, difference or symmetric difference of the languages.
+//!
+//! The alphabets are aligned first, a character outside the alphabet of one
+//! DFA is rejected by it. Only the pairs reachable from the start states
+//! become states of the product.
+
+use crate::DFA;
+use crate::symbol;
+use std::collections::HashMap;
+
+// *********************************************************************
+/// # How the accept states of a product are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Strings accepted by either DFA
+    Union,
+    /// Strings accepted by both DFAs
+    Intersection,
+    /// Strings accepted by the first DFA but not the second
+    Difference,
+    /// Strings accepted by exactly one of the DFAs
+    SymmetricDifference,
+}
+
+impl Operation {
+    /// Does a pair of states accept, given whether each of them accepts
+    pub fn accepts(self, first: bool, second: bool) -> bool {
+        match self {
+            Operation::Union => first || second,
+            Operation::Intersection => first && second,
+            Operation::Difference => first && !second,
+            Operation::SymmetricDifference => first != second,
+        }
+    }
+}
+
+// *********************************************************************
+/// Implement the product operations on the DFA structure
+impl DFA {
+
+    /// Return the complete DFA for the operation on the languages of both DFAs
+    ///
+    /// States are numbered in the order they are found, breadth first from
+    /// the pair of start states, so the start state is state 1.
+    pub fn product(&self, other: &DFA, op: Operation) -> Box<DFA> {
+
+        let alphabet = symbol::union(&self.alphabet, &other.alphabet);
+        let a = self.with_alphabet(&alphabet).complete();
+        let b = other.with_alphabet(&alphabet).complete();
+
+        // Pairs of states (1 relative) in the order found and their numbers
+        let mut pairs = vec![(a.start, b.start)];
+        let mut numbers = HashMap::new();
+        numbers.insert((a.start, b.start), 1);
+        let mut transitions = Vec::new();
+        let mut accept = Vec::new();
+        let mut n = 0;
+        while n < pairs.len() {
+            let (p, q) = pairs[n];
+            if op.accepts(a.accept.contains(&p), b.accept.contains(&q)) {
+                accept.push(n + 1);
+            }
+            let mut row = Vec::with_capacity(alphabet.len());
+            for col in 0..alphabet.len() {
+                let next = (a.transitions[p - 1][col].expect("complete DFA"),
+                            b.transitions[q - 1][col].expect("complete DFA"));
+                let number = *numbers.entry(next).or_insert_with(|| {
+                    pairs.push(next);
+                    pairs.len()
+                });
+                row.push(Some(number));
+            }
+            transitions.push(row);
+            n += 1;
+        }
+        Box::new(DFA{alphabet, start: 1, accept, transitions})
+    }
+
+    // *********************************************************************
+    /// Return a DFA accepting the strings accepted by either DFA
+    pub fn union(&self, other: &DFA) -> Box<DFA> {
+        self.product(other, Operation::Union)
+    }
+
+    /// Return a DFA accepting the strings accepted by both DFAs
+    pub fn intersection(&self, other: &DFA) -> Box<DFA> {
+        self.product(other, Operation::Intersection)
+    }
+
+    /// Return a DFA accepting the strings accepted by this DFA but not the other
+    pub fn difference(&self, other: &DFA) -> Box<DFA> {
+        self.product(other, Operation::Difference)
+    }
+
+    /// Return a DFA accepting the strings accepted by exactly one of the DFAs
+    pub fn symmetric_difference(&self, other: &DFA) -> Box<DFA> {
+        self.product(other, Operation::SymmetricDifference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{regex_dfa, same};
+
+    #[test]
+    fn operations() {
+        let (a, b) = (regex_dfa("a*b"), regex_dfa("[ab]b"));
+        assert!(same(&a.union(&b), "a*b|bb"));
+        assert!(same(&a.intersection(&b), "ab"));
+        assert!(same(&a.difference(&b), "b|aaa*b"));
+        assert!(same(&b.difference(&a), "bb"));
+        assert!(same(&a.symmetric_difference(&b), "b|aaa*b|bb"));
+        assert_eq!(a.intersection(&b).start, 1);
+        assert!(a.union(&b).is_complete());
+    }
+
+    #[test]
+    fn aligned_alphabets() {
+        // Strings over {a, b} ending in b, and a range that overlaps it
+        let ends = DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![1, 2], vec![1, 2]]);
+        let letters = regex_dfa("[a-c]+");
+        let both = ends.intersection(&letters);
+        assert_eq!(both.alphabet, symbol::union(&ends.alphabet, &letters.alphabet));
+        assert!(same(&both, "[ab]*b"));
+        assert!(same(&letters.difference(&ends), "[a-c]*(a|c)|[a-c]*c[a-c]*"));
+        assert!(same(&ends.union(&regex_dfa("")), "([ab]*b)?"));
+    }
+}
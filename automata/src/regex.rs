@@ -0,0 +1,867 @@
+//! Regular expression parser
+//!
+//! Recursive descent parser that turns a RegEx into a syntax tree.
+//! The grammar, from lowest to highest precedence:
+//!
+//! ```text
+//! alt     := concat ('|' concat)*
+//! concat  := (repeat | flags)*
+//! flags   := '(?i)' | '(?-i)'
+//! repeat  := atom ('*' | '+' | '?' | count)*
+//! count   := '{' n '}' | '{' m ',' '}' | '{' m ',' n '}'
+//! atom    := literal | '.' | escape | class | group | '^' | '$' | '\b' | '\B'
+//! group   := '(' alt ')' | '(?' '-'? 'i'? ':' alt ')'
+//! class   := '[' '^'? (char | char '-' char | escape)+ ']'
+//! escape  := '\' ('d' | 'w' | 's' | 'D' | 'W' | 'S' | operator)
+//! ```
+//!
+//! Each `(` starts a capture group, numbered from 1 in the order of the
+//! `(`s, and `(?:` starts a group that does not capture.
+//!
+//! `(?i)` makes the rest of the enclosing group case insensitive and `(?-i)`
+//! case sensitive again, `(?i:` and `(?-i:` start a group that does not
+//! capture with the case sensitivity set. Case insensitive literals and
+//! classes also match the simple case variants of their characters, the
+//! characters reached by changing the case one character at a time, so
+//! the automaton reads the input as it is.
+//!
+//! `^` and `$` match at the start and end of the string, `\b` between a word
+//! character and a non word character (or the start or end) and `\B`
+//! anywhere else, see [`Assertion`].
+//!
+//! Any Unicode character that is not an operator is a literal. The escapes
+//! `\d`, `\w` and `\s` are the ASCII digits, word characters and white space.
+//! The alphabet of an automaton for the RegEx is made of the ranges of
+//! characters the RegEx tells apart, see [`Regex::alphabet`].
+
+use crate::error::Error;
+use crate::symbol::{self, Symbol};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+// *********************************************************************
+/// # Syntax tree of a regular expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Regex {
+    /// Matches only the empty string
+    Empty,
+    /// Matches a single character
+    Literal(char),
+    /// Matches a single character of the class
+    Class(Class),
+    /// Matches each expression in sequence
+    Concat(Vec<Regex>),
+    /// Matches any one of the expressions
+    Alt(Vec<Regex>),
+    /// Matches zero or more repetitions of the expression
+    Star(Box<Regex>),
+    /// Matches one or more repetitions of the expression
+    Plus(Box<Regex>),
+    /// Matches the expression or the empty string
+    Optional(Box<Regex>),
+    /// Matches at least `min` and at most `max` (no limit if None) repetitions
+    Repeat { inner: Box<Regex>, min: u32, max: Option<u32> },
+    /// Matches the expression and captures it as group `index` (1 relative)
+    Group { index: usize, inner: Box<Regex> },
+    /// Matches the empty string where the assertion holds
+    Assert(Assertion),
+}
+
+// *********************************************************************
+/// # A condition on the characters around a position of the string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assertion {
+    /// `^` the start of the string
+    Start,
+    /// `$` the end of the string
+    End,
+    /// `\b` a word character on one side only
+    WordBoundary,
+    /// `\B` a word character on both sides or neither
+    NotWordBoundary,
+}
+
+// *********************************************************************
+/// # A set of characters from a bracket class, `.` or an escape
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    /// Match the characters not in the ranges instead
+    pub negated: bool,
+    /// Inclusive ranges of characters
+    pub ranges: Vec<(char, char)>,
+}
+
+/// Largest count allowed in `{m,n}`, every repetition is a copy of the automaton
+pub const MAX_REPEAT: u32 = 1000;
+
+/// Largest size a RegEx may have with its counts expanded, counting one for
+/// each character, class, assertion and operator, so nested counts such as
+/// `(a{1000}){1000}` can not build an automaton too large for memory
+pub const MAX_SIZE: u64 = 100_000;
+
+/// Ranges of the word characters matched by `\w` and used by `\b`
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+
+/// Is the character matched by `\w`
+pub fn is_word(ch: char) -> bool {
+    WORD.iter().any(|&(lo, hi)| lo <= ch && ch <= hi)
+}
+
+/// Characters that must be escaped with a backslash to be matched literally
+pub const OPERATORS: &[char] = &['(', ')', '*', '|', '+', '?', '.', '[', ']', '\\', '^', '-', '{', '}', '$'];
+
+impl Class {
+    /// Does the class match the character
+    pub fn matches(&self, ch: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi) != self.negated
+    }
+
+    /// The ranges of characters the class matches
+    pub fn matched_ranges(&self) -> Vec<(char, char)> {
+        if self.negated {
+            symbol::complement(&self.ranges)
+        } else {
+            self.ranges.clone()
+        }
+    }
+
+    /// The class for an escape letter, `\d` digits, `\w` word characters and
+    /// `\s` white space, upper case for the complement
+    fn escape(ch: char) -> Option<Class> {
+        let ranges = match ch.to_ascii_lowercase() {
+            'd' => vec![('0', '9')],
+            'w' => WORD.to_vec(),
+            's' => vec![('\t', '\r'), (' ', ' ')],
+            _ => return None,
+        };
+        Some(Class{negated: ch.is_ascii_uppercase(), ranges})
+    }
+}
+
+impl Assertion {
+    /// Does the assertion hold between the characters before and after a
+    /// position, given as whether each is a word character, None at the
+    /// start or end of the string
+    pub fn holds(&self, before: Option<bool>, after: Option<bool>) -> bool {
+        let boundary = before.unwrap_or(false) != after.unwrap_or(false);
+        match self {
+            Assertion::Start => before.is_none(),
+            Assertion::End => after.is_none(),
+            Assertion::WordBoundary => boundary,
+            Assertion::NotWordBoundary => !boundary,
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Assertion::Start => "^",
+            Assertion::End => "$",
+            Assertion::WordBoundary => "\\b",
+            Assertion::NotWordBoundary => "\\B",
+        })
+    }
+}
+
+impl Regex {
+    /// Return the sorted alphabet for an automaton of the RegEx
+    ///
+    /// The alphabet has a symbol for every literal and splits the
+    /// characters matched by the classes into the fewest ranges that no
+    /// literal or class only partly covers. A character the RegEx can not
+    /// match is in no symbol, so `.` makes the alphabet cover every
+    /// character while `ab` has just the symbols `a` and `b`.
+    pub fn alphabet(&self) -> Vec<Symbol> {
+        let mut ranges = Vec::new();
+        self.add_ranges(&mut ranges);
+        symbol::partition(&ranges)
+    }
+
+    fn add_ranges(&self, ranges: &mut Vec<(char, char)>) {
+        match self {
+            Regex::Empty | Regex::Assert(Assertion::Start) | Regex::Assert(Assertion::End) => {}
+            // Each symbol must be all word or all non word characters to tell boundaries
+            Regex::Assert(_) => ranges.extend_from_slice(WORD),
+            Regex::Literal(ch) => ranges.push((*ch, *ch)),
+            Regex::Class(class) => ranges.extend(class.matched_ranges()),
+            Regex::Concat(items) | Regex::Alt(items) =>
+                items.iter().for_each(|item| item.add_ranges(ranges)),
+            Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) |
+            Regex::Repeat{inner, ..} | Regex::Group{inner, ..} => inner.add_ranges(ranges),
+        }
+    }
+}
+
+// *********************************************************************
+/// Parse a RegEx into a syntax tree
+///
+/// Return Err with a description, the (0 relative) position of the
+/// offending character and what was expected there if the RegEx is not
+/// well formed, see [`caret`] to show the position.
+pub fn parse(pattern: &[char]) -> Result<Regex, Error> {
+    parse_with_case(pattern, false)
+}
+
+// *********************************************************************
+/// Parse a RegEx into a syntax tree, case insensitive from the start
+/// if `ignore_case` is true
+pub fn parse_with_case(pattern: &[char], ignore_case: bool) -> Result<Regex, Error> {
+    let mut parser = Parser { chars: pattern, pos: 0, groups: 0, ignore_case };
+    let re = parser.parse_alt()?;
+    match parser.peek() {
+        None => Ok(re),
+        Some(_) => Err(parser.error("Unmatched ')'".to_string(), "'|' or the end of the RegEx")),
+    }
+}
+
+// *********************************************************************
+/// Return the RegEx with a second line holding a `^` under the character
+/// at the (0 relative) position, or just past the end
+pub fn caret(pattern: &[char], pos: usize) -> String {
+    let pattern: String = pattern.iter().collect();
+    format!("{}\n{}^", pattern, " ".repeat(pos))
+}
+
+// *********************************************************************
+/// Position in the RegEx being parsed
+struct Parser<'a> {
+    /// Characters of the RegEx
+    chars: &'a [char],
+    /// Index of the next character to read
+    pos: usize,
+    /// Number of capture groups started so far
+    groups: usize,
+    /// Are literals and classes case insensitive at this point
+    ignore_case: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Error at the current position
+    fn error(&self, msg: String, expected: &str) -> Error {
+        Error::Parse{pos: self.pos, msg, expected: expected.to_string()}
+    }
+
+    /// Look at the next character without consuming it
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// alt := concat ('|' concat)*
+    fn parse_alt(&mut self) -> Result<Regex, Error> {
+        let start = self.pos;
+        let mut branches = vec![self.parse_concat()?];
+        // A branch of only flags has no items and is as empty as no branch
+        let mut empty = if branches[0] == Regex::Empty { Some(start) } else { None };
+        let mut total = size(&branches[0]);
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            let branch_start = self.pos;
+            branches.push(self.parse_concat()?);
+            total = total.saturating_add(size(&branches[branches.len() - 1]));
+            self.check_size(total, branch_start)?;
+            if branches[branches.len() - 1] == Regex::Empty && empty.is_none() {
+                empty = Some(branch_start);
+            }
+        }
+
+        // Write (a|) as a? to match the empty string
+        if let (Some(pos), true) = (empty, branches.len() > 1) {
+            return Err(Error::Parse{pos, msg: "Empty alternative".to_string(),
+                                    expected: "a character, class or group".to_string()});
+        }
+        if branches.len() == 1 {
+            Ok(branches.pop().unwrap())
+        } else {
+            Ok(Regex::Alt(branches))
+        }
+    }
+
+    /// concat := (repeat | flags)*
+    fn parse_concat(&mut self) -> Result<Regex, Error> {
+        let mut items = Vec::new();
+        let mut total: u64 = 0;
+        while let Some(ch) = self.peek() {
+            if ch == '|' || ch == ')' {
+                break;
+            }
+            let start = self.pos;
+            if !self.parse_flags() {
+                items.push(self.parse_repeat()?);
+                total = total.saturating_add(size(&items[items.len() - 1]));
+                self.check_size(total, start)?;
+            }
+        }
+        match items.len() {
+            0 => Ok(Regex::Empty),
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Regex::Concat(items)),
+        }
+    }
+
+    /// flags := '(?i)' | '(?-i)'
+    ///
+    /// Set the case sensitivity and return true if the flags are next
+    fn parse_flags(&mut self) -> bool {
+        let rest = &self.chars[self.pos..];
+        let (ignore_case, len) = match rest {
+            ['(', '?', 'i', ')', ..] => (true, 4),
+            ['(', '?', '-', 'i', ')', ..] => (false, 5),
+            _ => return false,
+        };
+        self.ignore_case = ignore_case;
+        self.pos += len;
+        true
+    }
+
+    /// repeat := atom ('*' | '+' | '?' | count)*
+    fn parse_repeat(&mut self) -> Result<Regex, Error> {
+        let mut re = self.parse_atom()?;
+        while let Some(op) = self.peek() {
+            if op == '{' {
+                re = self.parse_count(re)?;
+                continue;
+            }
+            // Repeating a repetition is at most a star, so a** is the same language as a*
+            re = match (op, re) {
+                ('*', Regex::Star(inner)) | ('*', Regex::Plus(inner)) | ('*', Regex::Optional(inner)) |
+                ('+', Regex::Optional(inner)) | ('?', Regex::Plus(inner)) => Regex::Star(inner),
+                ('+', re @ Regex::Star(_)) | ('+', re @ Regex::Plus(_)) |
+                ('?', re @ Regex::Star(_)) | ('?', re @ Regex::Optional(_)) => re,
+                ('*', re) => Regex::Star(Box::new(re)),
+                ('+', re) => Regex::Plus(Box::new(re)),
+                ('?', re) => Regex::Optional(Box::new(re)),
+                (_, re) => return Ok(re),
+            };
+            self.pos += 1;
+        }
+        Ok(re)
+    }
+
+    /// count := '{' n '}' | '{' m ',' '}' | '{' m ',' n '}'
+    ///
+    /// Counts that are the same as '*', '+', '?' or no repetition are
+    /// returned as those.
+    fn parse_count(&mut self, re: Regex) -> Result<Regex, Error> {
+        let open = self.pos;
+        self.pos += 1;
+        let min = self.parse_number()?;
+        let max = match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                if self.peek() == Some('}') { None } else { Some(self.parse_number()?) }
+            }
+            _ => Some(min),
+        };
+        if self.peek() != Some('}') {
+            return Err(self.error("Count not closed".to_string(), "',' or '}'"));
+        }
+        self.pos += 1;
+        if let Some(max) = max {
+            if min > max {
+                return Err(Error::Parse{pos: open,
+                                        msg: format!("Invalid count {{{},{}}}, {} is greater than {}",
+                                                     min, max, min, max),
+                                        expected: format!("a count of at least {}", min)});
+            }
+        }
+        let re = match (min, max) {
+            (0, None) => Regex::Star(Box::new(re)),
+            (1, None) => Regex::Plus(Box::new(re)),
+            (0, Some(1)) => Regex::Optional(Box::new(re)),
+            (1, Some(1)) => re,
+            (min, max) => Regex::Repeat{inner: Box::new(re), min, max},
+        };
+        self.check_size(size(&re), open)?;
+        Ok(re)
+    }
+
+    /// Return Err at pos if the size is over MAX_SIZE
+    fn check_size(&self, size: u64, pos: usize) -> Result<(), Error> {
+        if size > MAX_SIZE {
+            return Err(Error::Parse{pos, msg: format!("RegEx is over {} pieces with its counts expanded", MAX_SIZE),
+                                    expected: "smaller or fewer nested counts".to_string()});
+        }
+        Ok(())
+    }
+
+    /// A decimal number of at most MAX_REPEAT
+    fn parse_number(&mut self) -> Result<u32, Error> {
+        let start = self.pos;
+        let mut n: u32 = 0;
+        while let Some(d) = self.peek().and_then(|ch| ch.to_digit(10)) {
+            n = n.saturating_mul(10).saturating_add(d);
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.error("Count without a number".to_string(), "a digit"));
+        }
+        if n > MAX_REPEAT {
+            return Err(Error::Parse{pos: start, msg: format!("Count {} is over {}", n, MAX_REPEAT),
+                                    expected: format!("a number up to {}", MAX_REPEAT)});
+        }
+        Ok(n)
+    }
+
+    /// group := '(' alt ')' | '(?' '-'? 'i'? ':' alt ')'
+    ///
+    /// The case sensitivity set inside the group ends with it
+    fn parse_group(&mut self) -> Result<Regex, Error> {
+        let open = self.pos;
+        let saved = self.ignore_case;
+        self.pos += 1;
+        let index = if self.peek() == Some('?') {
+            self.pos += 1;
+            match (self.peek(), self.chars.get(self.pos + 1)) {
+                (Some('i'), _) => {
+                    self.ignore_case = true;
+                    self.pos += 1;
+                }
+                (Some('-'), Some('i')) => {
+                    self.ignore_case = false;
+                    self.pos += 2;
+                }
+                _ => {}
+            }
+            if self.peek() != Some(':') {
+                return Err(self.error("Unknown group flag".to_string(), "':', 'i:' or '-i:' after '(?'"));
+            }
+            self.pos += 1;
+            None
+        } else {
+            self.groups += 1;
+            Some(self.groups)
+        };
+        let re = self.parse_alt()?;
+        if self.peek() != Some(')') {
+            return Err(Error::Parse{pos: open, msg: "Unclosed '('".to_string(),
+                                    expected: format!("')' at position {}", self.pos)});
+        }
+        self.pos += 1;
+        self.ignore_case = saved;
+        Ok(match index {
+            Some(index) => Regex::Group{index, inner: Box::new(re)},
+            None => re,
+        })
+    }
+
+    /// atom := literal | '.' | escape | class | group | '^' | '$' | '\b' | '\B'
+    ///
+    /// A literal or class is case insensitive if the flag is set
+    fn parse_atom(&mut self) -> Result<Regex, Error> {
+        let re = match self.peek() {
+            Some('(') => return self.parse_group(),
+            Some('[') => self.parse_class()?,
+            Some('.') => {
+                self.pos += 1;
+                Regex::Class(Class{negated: true, ranges: vec!()})
+            }
+            Some('^') | Some('$') => {
+                let assertion = if self.peek() == Some('^') { Assertion::Start } else { Assertion::End };
+                self.pos += 1;
+                return Ok(Regex::Assert(assertion));
+            }
+            Some('\\') if matches!(self.chars.get(self.pos + 1), Some('b') | Some('B')) => {
+                let assertion = if self.chars[self.pos + 1] == 'b' { Assertion::WordBoundary }
+                                else { Assertion::NotWordBoundary };
+                self.pos += 2;
+                return Ok(Regex::Assert(assertion));
+            }
+            Some('\\') => match self.parse_escape()? {
+                Ok(ch) => Regex::Literal(ch),
+                Err(class) => Regex::Class(class),
+            },
+            Some(ch @ '*') | Some(ch @ '+') | Some(ch @ '?') | Some(ch @ '{') =>
+                return Err(self.error(format!("Nothing to repeat with '{}'", ch),
+                                      "a character, class or group before it")),
+            Some(ch) if OPERATORS.contains(&ch) =>
+                return Err(self.error(format!("Unexpected '{}'", ch), &format!("a character or '\\{}'", ch))),
+            Some(ch) => {
+                self.pos += 1;
+                Regex::Literal(ch)
+            }
+            None => return Err(self.error("Unexpected end of RegEx".to_string(), "a character, class or group")),
+        };
+        Ok(if self.ignore_case { fold_case(re) } else { re })
+    }
+
+    /// escape := '\' ('d' | 'w' | 's' | 'D' | 'W' | 'S' | operator)
+    ///
+    /// Return the escaped operator or the class of the escape letter
+    fn parse_escape(&mut self) -> Result<Result<char, Class>, Error> {
+        self.pos += 1;
+        match self.peek() {
+            Some(ch) if OPERATORS.contains(&ch) => {
+                self.pos += 1;
+                Ok(Ok(ch))
+            }
+            Some(ch) => match Class::escape(ch) {
+                Some(class) => {
+                    self.pos += 1;
+                    Ok(Err(class))
+                }
+                None => Err(self.error(format!("Unknown escape '\\{}'", ch),
+                                       "d, w, s, D, W, S or an operator after '\\'")),
+            },
+            None => Err(self.error("Unexpected end of RegEx after '\\'".to_string(),
+                                   "d, w, s, D, W, S or an operator")),
+        }
+    }
+
+    /// class := '[' '^'? (char | char '-' char | escape)+ ']'
+    fn parse_class(&mut self) -> Result<Regex, Error> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut class = Class{negated: false, ranges: vec!()};
+        if self.peek() == Some('^') {
+            class.negated = true;
+            self.pos += 1;
+        }
+        loop {
+            let lo_pos = self.pos;
+            let lo = match self.peek() {
+                None => return Err(Error::Parse{pos: open, msg: "Unclosed '['".to_string(),
+                                                expected: format!("']' at position {}", self.pos)}),
+                Some(']') if class.ranges.is_empty() =>
+                    return Err(self.error("Empty character class".to_string(), "a character or range")),
+                Some(']') => break,
+                Some('\\') => match self.parse_escape()? {
+                    Ok(ch) => ch,
+                    Err(inner) if !inner.negated => {
+                        class.ranges.extend(inner.ranges);
+                        continue;
+                    }
+                    Err(_) => return Err(self.error("Negated escape in a character class".to_string(),
+                                                    "\\d, \\w, \\s or a character")),
+                },
+                Some(ch) => {
+                    self.pos += 1;
+                    ch
+                }
+            };
+
+            // A '-' between two characters makes a range, otherwise it is literal
+            let mut hi = lo;
+            if self.peek() == Some('-') && !matches!(self.chars.get(self.pos + 1), None | Some(']')) {
+                self.pos += 1;
+                hi = match self.peek() {
+                    Some('\\') => match self.parse_escape()? {
+                        Ok(ch) => ch,
+                        Err(_) => return Err(self.error("Class escape ends a range".to_string(),
+                                                        "a character")),
+                    },
+                    Some(ch) => {
+                        self.pos += 1;
+                        ch
+                    }
+                    None => unreachable!(),
+                };
+                if hi < lo {
+                    return Err(Error::Parse{pos: lo_pos, msg: format!("Invalid range '{}-{}'", lo, hi),
+                                            expected: format!("a character from '{}' on", lo)});
+                }
+            }
+            class.ranges.push((lo, hi));
+        }
+        self.pos += 1;
+        Ok(Regex::Class(class))
+    }
+}
+
+// *********************************************************************
+/// Return the size of the RegEx with its counts expanded, one for each
+/// character, class, assertion and operator
+fn size(re: &Regex) -> u64 {
+    match re {
+        Regex::Empty | Regex::Literal(_) | Regex::Class(_) | Regex::Assert(_) => 1,
+        Regex::Concat(items) | Regex::Alt(items) => {
+            items.iter().fold(1, |total, item| total.saturating_add(size(item)))
+        }
+        Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) => size(inner).saturating_add(1),
+        Regex::Group{inner, ..} => size(inner),
+        // Each of the min copies, then one optional copy up to max or a star
+        Regex::Repeat{inner, min, max} => {
+            let copies = u64::from(max.unwrap_or(min + 1).max(*min));
+            size(inner).saturating_add(1).saturating_mul(copies)
+        }
+    }
+}
+
+// *********************************************************************
+/// Make a literal or class also match the case variants of its characters
+fn fold_case(re: Regex) -> Regex {
+    match re {
+        Regex::Literal(ch) => {
+            let variants = case_variants(ch);
+            if variants.len() == 1 {
+                return Regex::Literal(ch);
+            }
+            Regex::Class(Class{negated: false, ranges: merge(variants.iter().map(|&c| (c, c)).collect())})
+        }
+        Regex::Class(class) => {
+            let mut ranges = class.ranges.clone();
+            for &(lo, hi) in class.ranges.iter() {
+                for ch in lo..=hi {
+                    ranges.extend(case_variants(ch).into_iter().skip(1).map(|c| (c, c)));
+                }
+            }
+            Regex::Class(Class{negated: class.negated, ranges: merge(ranges)})
+        }
+        re => re,
+    }
+}
+
+/// Return the character followed by the characters of the same case orbit
+///
+/// Two characters are in the same orbit when one is the single character
+/// lower or upper case of the other, in either direction, and the orbit is
+/// closed under that. So σ, ς and Σ all match each other, as do k, K and
+/// the Kelvin sign. The orbits are those of Unicode simple case folding.
+fn case_variants(ch: char) -> Vec<char> {
+    let links = case_links();
+    let mut variants = vec![ch];
+    let mut i = 0;
+    while i < variants.len() {
+        for other in links.get(&variants[i]).into_iter().flatten() {
+            if !variants.contains(other) {
+                variants.push(*other);
+            }
+        }
+        i += 1;
+    }
+    variants
+}
+
+/// Return, for every character with one, the characters it is linked to by
+/// a single character case mapping in either direction
+///
+/// The dotless ı and dotted İ only fold to i and I in Turkic languages, so
+/// the mapping of ı to I is left out and they match only themselves.
+fn case_links() -> &'static HashMap<char, Vec<char>> {
+    const TURKIC: [char; 2] = ['\u{130}', '\u{131}'];
+    fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+        let ch = chars.next()?;
+        if chars.next().is_none() { Some(ch) } else { None }
+    }
+    static LINKS: OnceLock<HashMap<char, Vec<char>>> = OnceLock::new();
+    LINKS.get_or_init(|| {
+        let mut links: HashMap<char, Vec<char>> = HashMap::new();
+        for c in (0..=char::MAX as u32).filter_map(std::char::from_u32) {
+            if TURKIC.contains(&c) {
+                continue;
+            }
+            for &other in [single(c.to_lowercase()), single(c.to_uppercase())].iter().flatten() {
+                if other != c && !TURKIC.contains(&other) {
+                    links.entry(c).or_default().push(other);
+                    links.entry(other).or_default().push(c);
+                }
+            }
+        }
+        links
+    })
+}
+
+/// Sort the ranges and join the ones that overlap or touch
+fn merge(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(char, char)> = Vec::new();
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some(last) if lo as u32 <= last.1 as u32 + 1 => last.1 = last.1.max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(s: &str) -> Result<Regex, Error> {
+        parse(&s.chars().collect::<Vec<char>>())
+    }
+
+    fn lit(c: char) -> Regex {
+        Regex::Literal(c)
+    }
+
+    #[test]
+    fn precedence() {
+        // Star binds tighter than concatenation, which binds tighter than alternation
+        assert_eq!(parse_str("ab|c*").unwrap(),
+                   Regex::Alt(vec![Regex::Concat(vec![lit('a'), lit('b')]),
+                                   Regex::Star(Box::new(lit('c')))]));
+    }
+
+    fn group(index: usize, re: Regex) -> Regex {
+        Regex::Group{index, inner: Box::new(re)}
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(parse_str("(?:ab|c)*").unwrap(),
+                   Regex::Star(Box::new(Regex::Alt(vec![Regex::Concat(vec![lit('a'), lit('b')]),
+                                                        lit('c')]))));
+        assert_eq!(parse_str("(?:)").unwrap(), Regex::Empty);
+        assert_eq!(parse_str("(?:a|b)").unwrap(), Regex::Alt(vec![lit('a'), lit('b')]));
+        assert_eq!(parse_str("()").unwrap(), group(1, Regex::Empty));
+        assert_eq!(parse_str("(a(b))(?:c)(d)").unwrap(),
+                   Regex::Concat(vec![group(1, Regex::Concat(vec![lit('a'), group(2, lit('b'))])),
+                                      lit('c'), group(3, lit('d'))]));
+        assert_eq!(parse_str("(a)*").unwrap(), Regex::Star(Box::new(group(1, lit('a')))));
+    }
+
+    #[test]
+    fn errors() {
+        let pos = |s| match parse_str(s) {
+            Err(Error::Parse{pos, ..}) => Some(pos),
+            _ => None,
+        };
+        assert_eq!(pos("(ab"), Some(0));
+        assert_eq!(pos("ab)"), Some(2));
+        assert_eq!(pos(")("), Some(0));
+        assert_eq!(pos("*a"), Some(0));
+        assert_eq!(pos("a|*"), Some(2));
+        assert_eq!(pos("(?a)"), Some(2));
+        assert_eq!(pos("(?:a"), Some(0));
+        assert_eq!(pos("(a|)"), Some(3));
+        assert_eq!(pos("|a"), Some(0));
+        assert_eq!(pos("a||b"), Some(2));
+        assert_eq!(pos("a|"), Some(2));
+        assert_eq!(pos("a|(?i)"), Some(2));
+        assert_eq!(pos("(?i)|a"), Some(0));
+        assert_eq!(pos("(a|(?-i))"), Some(3));
+        assert_eq!(pos("(?i)a|b(?-i)"), None);
+        assert_eq!(pos("a-b"), Some(1));
+        assert_eq!(pos("\\q"), Some(1));
+        assert_eq!(pos("a\\"), Some(2));
+        assert_eq!(pos("a{2;3}"), Some(3));
+        assert_eq!(pos("a)b(c"), Some(1));
+        assert_eq!(parse_str("a|()").unwrap(), Regex::Alt(vec![lit('a'), group(1, Regex::Empty)]));
+        assert!(parse_str("").is_ok());
+
+        let err = parse_str("(a").unwrap_err();
+        assert_eq!(err.to_string(), "Unclosed '(' at position 0, expected ')' at position 2");
+        assert!(matches!(parse_str("a|+"), Err(Error::Parse{expected, ..})
+                                             if expected == "a character, class or group before it"));
+        assert_eq!(caret(&['a', '|', '+'], 2), "a|+\n  ^");
+    }
+
+    #[test]
+    fn repeats() {
+        assert_eq!(parse_str("ab+").unwrap(),
+                   Regex::Concat(vec![lit('a'), Regex::Plus(Box::new(lit('b')))]));
+        assert_eq!(parse_str("a?").unwrap(), Regex::Optional(Box::new(lit('a'))));
+        assert_eq!(parse_str("a+?").unwrap(), Regex::Star(Box::new(lit('a'))));
+        assert_eq!(parse_str("a*+").unwrap(), Regex::Star(Box::new(lit('a'))));
+    }
+
+    #[test]
+    fn classes() {
+        let class = |negated, ranges: &[(char, char)]| Regex::Class(Class{negated, ranges: ranges.to_vec()});
+        assert_eq!(parse_str("[a-f0-9]").unwrap(), class(false, &[('a', 'f'), ('0', '9')]));
+        assert_eq!(parse_str("[^x]").unwrap(), class(true, &[('x', 'x')]));
+        assert_eq!(parse_str("[a-]").unwrap(), class(false, &[('a', 'a'), ('-', '-')]));
+        assert_eq!(parse_str("[\\]\\d]").unwrap(), class(false, &[(']', ']'), ('0', '9')]));
+        assert_eq!(parse_str(".").unwrap(), class(true, &[]));
+        assert_eq!(parse_str("\\D").unwrap(), class(true, &[('0', '9')]));
+        assert_eq!(parse_str("\\(").unwrap(), lit('('));
+
+        let re = parse_str("x[^a-y]").unwrap();
+        assert_eq!(re.alphabet(), vec![Symbol::new('\0', '`'), 'x'.into(), Symbol::new('z', char::MAX)]);
+        assert_eq!(parse_str("b[a-c]é").unwrap().alphabet(), vec!['a'.into(), 'b'.into(), 'c'.into(), 'é'.into()]);
+    }
+
+    #[test]
+    fn class_errors() {
+        let pos = |s| match parse_str(s) {
+            Err(Error::Parse{pos, ..}) => Some(pos),
+            _ => None,
+        };
+        assert_eq!(pos("a[bc"), Some(1));
+        assert_eq!(pos("[]"), Some(1));
+        assert_eq!(pos("[z-a]"), Some(1));
+        assert_eq!(pos("[a0-9z-b]"), Some(5));
+        assert_eq!(pos("\\q"), Some(1));
+        assert_eq!(pos("a\\"), Some(2));
+        assert_eq!(pos("a+*?"), None);
+        assert_eq!(pos("+a"), Some(0));
+    }
+
+    #[test]
+    fn counts() {
+        let a = || Box::new(lit('a'));
+        assert_eq!(parse_str("a{3}").unwrap(), Regex::Repeat{inner: a(), min: 3, max: Some(3)});
+        assert_eq!(parse_str("a{2,}").unwrap(), Regex::Repeat{inner: a(), min: 2, max: None});
+        assert_eq!(parse_str("a{2,5}").unwrap(), Regex::Repeat{inner: a(), min: 2, max: Some(5)});
+        assert_eq!(parse_str("a{0,}").unwrap(), Regex::Star(a()));
+        assert_eq!(parse_str("a{0,1}").unwrap(), Regex::Optional(a()));
+        assert_eq!(parse_str("a{1}").unwrap(), lit('a'));
+
+        let pos = |s| match parse_str(s) {
+            Err(Error::Parse{pos, ..}) => Some(pos),
+            _ => None,
+        };
+        assert_eq!(pos("a{3,2}"), Some(1));
+        assert_eq!(pos("a{,2}"), Some(2));
+        assert_eq!(pos("a{2"), Some(3));
+        assert_eq!(pos("a{2,x}"), Some(4));
+        assert_eq!(pos("{2}"), Some(0));
+        assert_eq!(pos("a{1001}"), Some(2));
+
+        // Nested and repeated counts are limited by their expanded size
+        assert_eq!(pos("(a{1000}){1000}"), Some(9));
+        assert_eq!(pos("((a{1000}){1000}){1000}"), Some(10));
+        assert_eq!(pos("(a{1000}){40}"), None);
+        assert_eq!(pos("(a{1000}){40}(b{1000}){40}"), Some(13));
+        assert_eq!(pos("(a{1000}){40}|(b{1000}){40}"), Some(14));
+    }
+
+    #[test]
+    fn assertions() {
+        let assert = |a| Regex::Assert(a);
+        assert_eq!(parse_str("^a$").unwrap(),
+                   Regex::Concat(vec![assert(Assertion::Start), lit('a'), assert(Assertion::End)]));
+        assert_eq!(parse_str("\\ba\\B").unwrap(),
+                   Regex::Concat(vec![assert(Assertion::WordBoundary), lit('a'),
+                                      assert(Assertion::NotWordBoundary)]));
+        assert!(parse_str("[\\b]").is_err());
+        assert!(Assertion::WordBoundary.holds(None, Some(true)));
+        assert!(!Assertion::WordBoundary.holds(Some(false), None));
+        assert!(Assertion::NotWordBoundary.holds(Some(true), Some(true)));
+        assert_eq!(parse_str("\\b").unwrap().alphabet().len(), 4);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let class = |ranges: &[(char, char)]| Regex::Class(Class{negated: false, ranges: ranges.to_vec()});
+        let parse_ci = |s: &str| parse_with_case(&s.chars().collect::<Vec<char>>(), true).unwrap();
+        assert_eq!(parse_ci("a1"), Regex::Concat(vec![class(&[('A', 'A'), ('a', 'a')]), lit('1')]));
+        assert_eq!(parse_str("a(?i)b|c").unwrap(),
+                   Regex::Alt(vec![Regex::Concat(vec![lit('a'), class(&[('B', 'B'), ('b', 'b')])]),
+                                   class(&[('C', 'C'), ('c', 'c')])]));
+        assert_eq!(parse_str("(?i:a)a").unwrap(),
+                   Regex::Concat(vec![class(&[('A', 'A'), ('a', 'a')]), lit('a')]));
+        assert_eq!(parse_ci("(?-i)a(?i:b)"),
+                   Regex::Concat(vec![lit('a'), class(&[('B', 'B'), ('b', 'b')])]));
+        assert_eq!(parse_ci("(?-i:w)"), lit('w'));
+        assert_eq!(parse_ci("[x-z]"), class(&[('X', 'Z'), ('x', 'z')]));
+        assert_eq!(parse_ci("[^é]"), Regex::Class(Class{negated: true, ranges: vec![('É', 'É'), ('é', 'é')]}));
+        assert_eq!(parse_ci("ß"), class(&[('ß', 'ß'), ('ẞ', 'ẞ')]));
+        assert_eq!(parse_ci("ẞ"), parse_ci("ß"));
+        assert_eq!(parse_ci("σ"), class(&[('Σ', 'Σ'), ('ς', 'σ')]));
+        assert_eq!(parse_ci("ς"), parse_ci("Σ"));
+        assert_eq!(parse_ci("k"), class(&[('K', 'K'), ('k', 'k'), ('\u{212A}', '\u{212A}')]));
+        assert_eq!(parse_ci("\u{212A}"), parse_ci("K"));
+        assert_eq!(parse_ci("s"), class(&[('S', 'S'), ('s', 's'), ('ſ', 'ſ')]));
+        assert_eq!(parse_ci("ſ"), parse_ci("s"));
+        assert_eq!(parse_ci("i"), class(&[('I', 'I'), ('i', 'i')]));
+        assert_eq!(parse_ci("I"), parse_ci("i"));
+        assert_eq!(parse_ci("ı"), lit('ı'));
+        assert_eq!(parse_ci("İ"), lit('İ'));
+        assert_eq!(parse_ci("1"), lit('1'));
+        assert!(parse_str("(?x)").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Searching for matches inside a string
+//!
+//! The DFA is run from each position of the string in turn, the first
+//! position where it reaches an accept state gives the leftmost match and
+//! the last accept state reached from there gives the longest. A run stops
+//! as soon as no accept state can be reached any more, or at a character
+//! outside the alphabet.
+//!
+//! A DFA run from the middle of a string behaves as if the string started
+//! there, so for a RegEx with `^`, `$`, `\b` or `\B` search the NFA with
+//! [`NFA::find`](crate::NFA::find) and [`NFA::find_iter`](crate::NFA::find_iter)
+//! instead.
+
+use crate::graph::StateGraph;
+
+// *********************************************************************
+/// # Byte offsets of a match in the searched string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Offset of the first byte of the match
+    pub start: usize,
+    /// Offset just past the last byte of the match
+    pub end: usize,
+}
+
+impl Match {
+    /// Return the matched part of the searched string
+    pub fn as_str<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.start..self.end]
+    }
+}
+
+// *********************************************************************
+/// # Iterator over the non-overlapping matches in a string
+pub struct Matches<'t, F> {
+    /// Find the next match at or after an offset
+    find: F,
+    text: &'t str,
+    /// Byte offset to search from next, past the end when finished
+    next: usize,
+}
+
+impl<'t, F> Matches<'t, F> where F: Fn(&str, usize) -> Option<Match> {
+    /// Iterate over the matches found by find
+    pub fn new(text: &'t str, find: F) -> Matches<'t, F> {
+        Matches{find, text, next: 0}
+    }
+}
+
+impl<'t, F> Iterator for Matches<'t, F> where F: Fn(&str, usize) -> Option<Match> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        if self.next > self.text.len() {
+            return None;
+        }
+        let found = (self.find)(self.text, self.next);
+        self.next = match found {
+            // After an empty match step over a character so it is not found again
+            Some(m) if m.start == m.end => match self.text[m.end..].chars().next() {
+                Some(ch) => m.end + ch.len_utf8(),
+                None => self.text.len() + 1,
+            },
+            Some(m) => m.end,
+            None => self.text.len() + 1,
+        };
+        found
+    }
+}
+
+// *********************************************************************
+/// Implement searching on the State Graph structure
+impl StateGraph {
+
+    /// Return the states (0 relative) from which an accept state can be reached
+    pub fn live_states(&self) -> Vec<bool> {
+        let mut live: Vec<bool> = self.states.iter().map(|s| s.accept_state).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (n, state) in self.states.iter().enumerate() {
+                if !live[n] && state.transitions.iter().any(|&to| live[to]) {
+                    live[n] = true;
+                    changed = true;
+                }
+            }
+        }
+        live
+    }
+
+    // *********************************************************************
+    /// Return the leftmost-longest match starting at or after byte offset from
+    pub fn find(&self, text: &str, from: usize) -> Option<Match> {
+        self.find_live(text, from, &self.live_states())
+    }
+
+    // *********************************************************************
+    /// Return an iterator over the leftmost-longest non-overlapping matches
+    pub fn find_iter<'g, 't>(&'g self, text: &'t str)
+                             -> Matches<'t, impl Fn(&str, usize) -> Option<Match> + 'g> {
+        let live = self.live_states();
+        Matches::new(text, move |text: &str, from| self.find_live(text, from, &live))
+    }
+
+    // *********************************************************************
+    /// Find with the live states already known
+    fn find_live(&self, text: &str, from: usize, live: &[bool]) -> Option<Match> {
+
+        if !live[self.start_state] {
+            return None;
+        }
+        let starts = text[from..].char_indices().map(|(i, _)| from + i)
+                                 .chain(std::iter::once(text.len()));
+        for start in starts {
+            // Run the DFA as far as an accept state can still be reached
+            let mut state = self.start_state;
+            let mut end = if self.states[state].accept_state { Some(start) } else { None };
+            for (i, ch) in text[start..].char_indices() {
+                state = match self.classes.class(ch) {
+                    Some(class) => self.states[state].transitions[class],
+                    None => break,
+                };
+                if !live[state] {
+                    break;
+                }
+                if self.states[state].accept_state {
+                    end = Some(start + i + ch.len_utf8());
+                }
+            }
+            if let Some(end) = end {
+                return Some(Match{start, end});
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::regex_graph;
+
+    fn spans(graph: &StateGraph, text: &str) -> Vec<(usize, usize)> {
+        graph.find_iter(text).map(|m| (m.start, m.end)).collect()
+    }
+
+    #[test]
+    fn leftmost_longest() {
+        let graph = regex_graph("ab*|b");
+        assert_eq!(graph.find("xxabbbab", 0), Some(Match{start: 2, end: 6}));
+        assert_eq!(graph.find("xxabbbab", 3), Some(Match{start: 3, end: 4}));
+        assert_eq!(graph.find("xyz", 0), None);
+        assert_eq!(spans(&graph, "abbxbaé"), vec![(0, 3), (4, 5), (5, 6)]);
+        assert_eq!(graph.find("ééab", 0).unwrap().as_str("ééab"), "ab");
+    }
+
+    #[test]
+    fn empty_matches() {
+        let graph = regex_graph("a*");
+        assert_eq!(spans(&graph, "baaé"), vec![(0, 0), (1, 3), (3, 3), (5, 5)]);
+        assert_eq!(spans(&graph, ""), vec![(0, 0)]);
+        assert_eq!(spans(&regex_graph("a"), ""), vec![]);
+    }
+}
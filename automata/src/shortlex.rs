@@ -0,0 +1,192 @@
+//! Listing strings in shortlex order
+//!
+//! Strings are listed shortest first, and in character order among those
+//! of the same length. For each length the strings are found by a depth
+//! first search that only follows a transition if a wanted state can still
+//! be reached by reading exactly the characters left, so no time is spent
+//! on strings that are not listed.
+//!
+//! Only strings over the alphabet are listed. A DFA with n states that
+//! accepts a string of length at least n accepts infinitely many, so the
+//! search stops once n lengths in a row past that point have no strings.
+
+use crate::graph::StateGraph;
+use crate::symbol::Symbol;
+
+// *********************************************************************
+/// # Iterator over the accepted or rejected strings in shortlex order
+pub struct Strings<'g> {
+    graph: &'g StateGraph,
+    /// Symbols in character order with their class
+    symbols: Vec<(Symbol, usize)>,
+    /// For each count k, the states (0 relative) from which a wanted state,
+    /// accepting or not as asked for, is reached by reading exactly k characters
+    reach: Vec<Vec<bool>>,
+    /// Length of the strings being listed
+    length: usize,
+    /// Longest strings to list, if limited
+    max: Option<usize>,
+    /// Length from which a string means there are infinitely many
+    long: usize,
+    /// Has a string at least long characters long been listed
+    found_long: bool,
+    /// Has the first string of the current length been looked for
+    started: bool,
+    /// The current string as the state before each character, the index
+    /// of its symbol and the character
+    stack: Vec<(usize, usize, char)>,
+}
+
+impl<'g> Strings<'g> {
+
+    /// Return the state (0 relative) reached by the current string
+    fn state(&self) -> usize {
+        match self.stack.last() {
+            Some(&(state, sym, _)) => self.target(state, sym),
+            None => self.graph.start_state,
+        }
+    }
+
+    /// Return the state (0 relative) reached from state on the symbol at index sym
+    fn target(&self, state: usize, sym: usize) -> usize {
+        self.graph.states[state].transitions[self.symbols[sym].1]
+    }
+
+    /// Compute the reachable states for every count up to k
+    fn extend_reach(&mut self, k: usize) {
+        while self.reach.len() <= k {
+            let last = &self.reach[self.reach.len() - 1];
+            let next = self.graph.states.iter()
+                           .map(|state| state.transitions.iter().any(|&to| last[to]))
+                           .collect();
+            self.reach.push(next);
+        }
+    }
+
+    /// Return the first symbol (index) at or after sym whose character leads
+    /// from state to one of the states that reach a wanted state in remaining - 1
+    fn first_symbol(&self, state: usize, remaining: usize, sym: usize) -> Option<usize> {
+        (sym..self.symbols.len()).find(|&j| self.reach[remaining - 1][self.target(state, j)])
+    }
+
+    /// Complete the current string with the smallest characters that still
+    /// lead to a wanted state
+    fn fill(&mut self) {
+        while self.stack.len() < self.length {
+            let state = self.state();
+            let sym = self.first_symbol(state, self.length - self.stack.len(), 0)
+                          .expect("a wanted state can be reached");
+            self.stack.push((state, sym, self.symbols[sym].0.lo));
+        }
+    }
+
+    /// Move to the next string of the current length, false if there is none
+    fn advance(&mut self) -> bool {
+        while let Some((state, sym, ch)) = self.stack.pop() {
+            // The next character of the same symbol leads to the same state
+            let next = if ch < self.symbols[sym].0.hi {
+                Some((sym, successor(ch)))
+            } else {
+                let remaining = self.length - self.stack.len();
+                self.first_symbol(state, remaining, sym + 1).map(|j| (j, self.symbols[j].0.lo))
+            };
+            if let Some((sym, ch)) = next {
+                self.stack.push((state, sym, ch));
+                self.fill();
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<'g> Iterator for Strings<'g> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if self.max.is_some_and(|max| self.length > max)
+               || (self.length >= self.long + self.graph.states.len() && !self.found_long) {
+                return None;
+            }
+            let found = if self.started {
+                self.advance()
+            } else {
+                self.started = true;
+                self.extend_reach(self.length);
+                if self.reach[self.length][self.graph.start_state] {
+                    self.fill();
+                    true
+                } else {
+                    false
+                }
+            };
+            if found {
+                self.found_long |= self.length >= self.long;
+                return Some(self.stack.iter().map(|&(_, _, ch)| ch).collect());
+            }
+            self.length += 1;
+            self.started = false;
+        }
+    }
+}
+
+// *********************************************************************
+/// Return the character after ch, skipping the surrogate code points
+fn successor(ch: char) -> char {
+    match ch {
+        '\u{D7FF}' => '\u{E000}',
+        _ => std::char::from_u32(ch as u32 + 1).expect("a character"),
+    }
+}
+
+// *********************************************************************
+/// Implement shortlex listing on the State Graph structure
+impl StateGraph {
+
+    /// Return an iterator over the accepted (or, if accepted is false, the
+    /// rejected) strings of min to max characters in shortlex order
+    ///
+    /// The strings are found as they are asked for, so use
+    /// [`take`](Iterator::take) for the first few of an infinite language.
+    pub fn strings(&self, accepted: bool, min: usize, max: Option<usize>) -> Strings<'_> {
+
+        let mut symbols: Vec<(Symbol, usize)> = self.alphabet.iter().copied().zip(self.columns.iter().copied())
+                                                    .collect();
+        symbols.sort_unstable();
+        let wanted = self.states.iter().map(|s| s.accept_state == accepted).collect();
+        Strings{graph: self, symbols, reach: vec![wanted], length: min, max,
+                long: min.max(self.states.len()), found_long: false, started: false, stack: Vec::new()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{StateGraph, DFA};
+    use crate::testing::regex_graph;
+
+    fn list(graph: &StateGraph, accepted: bool, min: usize, max: Option<usize>, k: usize) -> Vec<String> {
+        graph.strings(accepted, min, max).take(k).collect()
+    }
+
+    #[test]
+    fn accepted() {
+        let graph = regex_graph("(b|a)(a|b)*c?");
+        assert_eq!(list(&graph, true, 0, None, 7), vec!["a", "b", "aa", "ab", "ac", "ba", "bb"]);
+        assert_eq!(list(&graph, true, 3, Some(3), 3), vec!["aaa", "aab", "aac"]);
+        assert_eq!(list(&regex_graph("[x-z]|é"), true, 0, None, 10), vec!["x", "y", "z", "é"]);
+        assert_eq!(list(&regex_graph("x{0}"), true, 0, None, 10), vec![""]);
+        assert_eq!(list(&regex_graph("ab|c"), true, 5, None, 10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejected() {
+        // Strings over {a, b} ending in b
+        let dfa = DFA::new(vec!['b', 'a'], 1, vec![2], vec![vec![2, 1], vec![2, 1]]);
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert_eq!(list(&graph, false, 0, None, 4), vec!["", "a", "aa", "ba"]);
+        assert_eq!(list(&graph, false, 0, Some(1), 10), vec!["", "a"]);
+        assert_eq!(list(&graph, true, 2, None, 2), vec!["ab", "bb"]);
+        assert_eq!(list(&regex_graph("a*"), false, 0, None, 10), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,219 @@
+//! Alphabet symbols
+//!
+//! Each column of a transition matrix is labeled with a range of
+//! characters, so a single column can stand for all of `a-z` or for every
+//! character a RegEx does not mention. In yaml a symbol is a single
+//! character `'x'` or a range `'a-z'`, any Unicode character may be used:
+//!
+//! ```yaml
+//! alphabet: ['a-z', 'A-Z', 'é', '-']
+//! ```
+
+use crate::error::Error;
+use serde::{Deserialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+// *********************************************************************
+/// # An inclusive range of characters labeling one column
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Symbol {
+    /// First character of the range
+    pub lo: char,
+    /// Last character of the range
+    pub hi: char,
+}
+
+impl Symbol {
+    /// Create the symbol for the characters lo through hi
+    pub fn new(lo: char, hi: char) -> Symbol {
+        Symbol{lo, hi}
+    }
+
+    /// Does the symbol stand for the character
+    pub fn contains(&self, ch: char) -> bool {
+        self.lo <= ch && ch <= self.hi
+    }
+
+    /// Do the two symbols share a character
+    pub fn overlaps(&self, other: &Symbol) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    /// Return the number of characters the symbol stands for
+    pub fn size(&self) -> u32 {
+        // Surrogate code points are not characters
+        let surrogates = Symbol::new('\u{D7FF}', '\u{E000}');
+        let gap = if self.lo <= surrogates.lo && self.hi >= surrogates.hi { 0x800 } else { 0 };
+        self.hi as u32 - self.lo as u32 + 1 - gap
+    }
+}
+
+impl From<char> for Symbol {
+    fn from(ch: char) -> Symbol {
+        Symbol{lo: ch, hi: ch}
+    }
+}
+
+impl TryFrom<String> for Symbol {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Symbol, String> {
+        let chars: Vec<char> = s.chars().collect();
+        match chars[..] {
+            [ch] => Ok(Symbol::from(ch)),
+            [lo, '-', hi] if lo <= hi => Ok(Symbol{lo, hi}),
+            _ => Err(format!("Symbol '{}' should be a character or a range like 'a-z'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.lo == self.hi {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "{}-{}", self.lo, self.hi)
+        }
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.lo == self.hi {
+            write!(f, "{:?}", self.lo)
+        } else {
+            write!(f, "{:?}-{:?}", self.lo, self.hi)
+        }
+    }
+}
+
+// *********************************************************************
+/// An alphabet with one symbol for each of the characters
+pub fn singles(chars: &[char]) -> Vec<Symbol> {
+    chars.iter().map(|&ch| Symbol::from(ch)).collect()
+}
+
+// *********************************************************************
+/// Return the column (0 relative) of the symbol containing the character
+pub fn column(alphabet: &[Symbol], ch: char) -> Option<usize> {
+    alphabet.iter().position(|s| s.contains(ch))
+}
+
+// *********************************************************************
+/// Check that no character is in two symbols of the alphabet
+///
+/// Return Err with the (1 relative) columns of the first overlap found.
+pub fn check_disjoint(alphabet: &[Symbol]) -> Result<(), Error> {
+    for (i, a) in alphabet.iter().enumerate() {
+        if let Some(j) = alphabet[i + 1..].iter().position(|b| a.overlaps(b)) {
+            return Err(Error::OverlappingSymbols{first: i + 1, second: i + j + 2});
+        }
+    }
+    Ok(())
+}
+
+// *********************************************************************
+/// Return the sorted symbols that tell the ranges apart
+///
+/// The result splits the characters covered by the ranges at every point
+/// where a range starts or ends, so each range is a union of the symbols
+/// and no symbol is partly inside a range. Characters not in any range
+/// are left out.
+pub fn partition(ranges: &[(char, char)]) -> Vec<Symbol> {
+
+    // Every range starts a piece at lo and another after hi
+    let mut cuts: Vec<u32> = Vec::new();
+    for &(lo, hi) in ranges {
+        cuts.push(lo as u32);
+        cuts.push(hi as u32 + 1);
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut symbols = Vec::new();
+    for pair in cuts.windows(2) {
+        // Step over the surrogates, which are not characters
+        let lo = char::from_u32(pair[0]).unwrap_or('\u{E000}');
+        let hi = char::from_u32(pair[1] - 1).unwrap_or('\u{D7FF}');
+        if lo <= hi && ranges.iter().any(|&(rlo, rhi)| rlo <= lo && lo <= rhi) {
+            symbols.push(Symbol{lo, hi});
+        }
+    }
+    symbols
+}
+
+// *********************************************************************
+/// Return the sorted alphabet that refines both alphabets
+///
+/// Each symbol of the result is inside one symbol of each alphabet or
+/// outside all the symbols of that alphabet.
+pub fn union(a: &[Symbol], b: &[Symbol]) -> Vec<Symbol> {
+    let ranges: Vec<(char, char)> = a.iter().chain(b).map(|s| (s.lo, s.hi)).collect();
+    partition(&ranges)
+}
+
+// *********************************************************************
+/// Return the sorted ranges of the characters not in any of the ranges
+pub fn complement(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+    let mut gaps = Vec::new();
+    let mut next = 0u32;        // first character not yet covered
+    for (lo, hi) in sorted {
+        if (lo as u32) > next {
+            gaps.push((next, lo as u32 - 1));
+        }
+        next = next.max(hi as u32 + 1);
+    }
+    if next <= char::MAX as u32 {
+        gaps.push((next, char::MAX as u32));
+    }
+    gaps.into_iter()
+        .filter_map(|(lo, hi)| {
+            let lo = char::from_u32(lo).unwrap_or('\u{E000}');
+            let hi = char::from_u32(hi).unwrap_or('\u{D7FF}');
+            if lo <= hi { Some((lo, hi)) } else { None }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_yaml() {
+        let alphabet: Vec<Symbol> = serde_yaml::from_str("['a-z', 'é', '-', '中']").unwrap();
+        assert_eq!(alphabet, vec![Symbol::new('a', 'z'), 'é'.into(), '-'.into(), '中'.into()]);
+        assert_eq!(alphabet[0].to_string(), "a-z");
+        assert_eq!(column(&alphabet, 'q'), Some(0));
+        assert_eq!(column(&alphabet, '中'), Some(3));
+        assert_eq!(column(&alphabet, 'A'), None);
+        assert!(serde_yaml::from_str::<Symbol>("'z-a'").is_err());
+        assert!(serde_yaml::from_str::<Symbol>("'ab'").is_err());
+        assert!(matches!(check_disjoint(&[Symbol::new('a', 'f'), 'x'.into(), 'c'.into()]),
+                         Err(Error::OverlappingSymbols{first: 1, second: 3})));
+    }
+
+    #[test]
+    fn partition_ranges() {
+        assert_eq!(partition(&[('a', 'z'), ('x', 'x')]),
+                   vec![Symbol::new('a', 'w'), 'x'.into(), Symbol::new('y', 'z')]);
+        assert_eq!(partition(&[('a', 'a'), ('c', 'c')]), singles(&['a', 'c']));
+        assert_eq!(partition(&complement(&[])), vec![Symbol::new('\0', char::MAX)]);
+        assert_eq!(partition(&[('\0', '\u{D7FF}'), ('\0', char::MAX)]),
+                   vec![Symbol::new('\0', '\u{D7FF}'), Symbol::new('\u{E000}', char::MAX)]);
+        assert_eq!(complement(&[('b', 'c'), ('\0', 'a')]), vec![('d', char::MAX)]);
+        assert_eq!(Symbol::new('a', 'z').size(), 26);
+        assert_eq!(Symbol::new('\0', char::MAX).size(), 0x110000 - 0x800);
+        assert_eq!(Symbol::new('\u{D7FF}', '\u{E000}').size(), 2);
+        assert_eq!(Symbol::new('\u{D7FF}', '\u{E001}').size(), 3);
+        assert_eq!(Symbol::new('\u{D7FE}', '\u{E000}').size(), 3);
+        assert_eq!(Symbol::from('\u{D7FF}').size(), 1);
+        assert_eq!(Symbol::from('\u{E000}').size(), 1);
+        assert_eq!(union(&[Symbol::new('a', 'c')], &singles(&['b', 'x'])),
+                   vec!['a'.into(), 'b'.into(), 'c'.into(), 'x'.into()]);
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers shared by the unit tests
+
+use crate::{DFA, StateGraph};
+
+/// Return the DFA for a RegEx that must parse
+pub fn regex_dfa(pattern: &str) -> Box<DFA> {
+    DFA::from_regex(pattern).unwrap()
+}
+
+/// Return the state graph for a RegEx that must parse
+pub fn regex_graph(pattern: &str) -> Box<StateGraph> {
+    StateGraph::new_from_dfa(&regex_dfa(pattern))
+}
+
+/// Is the DFA valid and does it accept the same strings as the RegEx
+pub fn same(dfa: &DFA, pattern: &str) -> bool {
+    dfa.validate().is_ok() && dfa.distinguish(&regex_dfa(pattern)).is_none()
+}
@@ -0,0 +1,284 @@
+//! Thompson construction of an NFA from a regular expression
+//!
+//! Every sub expression becomes a fragment with a single start and a
+//! single end state, and fragments are glued together with epsilon
+//! transitions.
+
+use crate::nfa::NFA;
+use crate::regex::Regex;
+use crate::symbol::Symbol;
+
+// *********************************************************************
+/// Start and end state (1 relative) of a piece of the NFA under construction
+struct Fragment {
+    start: usize,
+    end: usize,
+}
+
+impl NFA {
+
+    /// Create an NFA with no states over the alphabet given
+    fn new(alphabet: &[Symbol]) -> NFA {
+        NFA{alphabet: alphabet.to_vec(), start: 1, accept: vec!(),
+            transitions: vec!(), epsilon: vec!(), assertions: vec!()}
+    }
+
+    // *********************************************************************
+    /// Add a state with no transitions and return its (1 relative) number
+    fn add_state(&mut self) -> usize {
+        self.transitions.push(vec![vec!(); self.alphabet.len()]);
+        self.epsilon.push(vec!());
+        self.transitions.len()
+    }
+
+    // *********************************************************************
+    /// Add a transition from state `from` to state `to` on alphabet column `col`
+    fn add_transition(&mut self, from: usize, col: usize, to: usize) {
+        let cell = &mut self.transitions[from - 1][col];
+        if !cell.contains(&to) {
+            cell.push(to);
+        }
+    }
+
+    // *********************************************************************
+    /// Add an epsilon transition from state `from` to state `to`
+    fn add_epsilon(&mut self, from: usize, to: usize) {
+        let cell = &mut self.epsilon[from - 1];
+        if !cell.contains(&to) {
+            cell.push(to);
+        }
+    }
+
+    // *********************************************************************
+    /// Build an NFA accepting the language of the RegEx syntax tree
+    ///
+    /// Every literal in the RegEx must be a symbol of the alphabet given and
+    /// each symbol must be all in or all out of every class, as is the case
+    /// for the alphabet from [`Regex::alphabet`].
+    pub fn new_from_regex(re: &Regex, alphabet: &[Symbol]) -> NFA {
+        let mut nfa = NFA::new(alphabet);
+        let frag = nfa.add_regex(re);
+        nfa.start = frag.start;
+        nfa.accept.push(frag.end);
+        nfa
+    }
+
+    // *********************************************************************
+    /// Add the states for a sub expression and return its fragment
+    fn add_regex(&mut self, re: &Regex) -> Fragment {
+        match re {
+            Regex::Empty => {
+                let start = self.add_state();
+                let end = self.add_state();
+                self.add_epsilon(start, end);
+                Fragment{start, end}
+            }
+            Regex::Literal(ch) => {
+                let col = self.alphabet.iter().position(|&s| s == Symbol::from(*ch))
+                              .expect("RegEx literal not in alphabet");
+                let start = self.add_state();
+                let end = self.add_state();
+                self.add_transition(start, col, end);
+                Fragment{start, end}
+            }
+            Regex::Class(class) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                for col in 0..self.alphabet.len() {
+                    if class.matches(self.alphabet[col].lo) {
+                        self.add_transition(start, col, end);
+                    }
+                }
+                Fragment{start, end}
+            }
+            Regex::Concat(items) => {
+                let mut frags = items.iter().map(|item| self.add_regex(item)).collect::<Vec<_>>();
+                for i in 1..frags.len() {
+                    self.add_epsilon(frags[i - 1].end, frags[i].start);
+                }
+                let last = frags.pop().unwrap();
+                Fragment{start: frags.first().map_or(last.start, |f| f.start), end: last.end}
+            }
+            Regex::Alt(branches) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                for branch in branches {
+                    let frag = self.add_regex(branch);
+                    self.add_epsilon(start, frag.start);
+                    self.add_epsilon(frag.end, end);
+                }
+                Fragment{start, end}
+            }
+            Regex::Star(inner) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                let frag = self.add_regex(inner);
+                self.add_epsilon(start, frag.start);
+                self.add_epsilon(start, end);
+                self.add_epsilon(frag.end, frag.start);
+                self.add_epsilon(frag.end, end);
+                Fragment{start, end}
+            }
+            Regex::Plus(inner) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                let frag = self.add_regex(inner);
+                self.add_epsilon(start, frag.start);
+                self.add_epsilon(frag.end, frag.start);
+                self.add_epsilon(frag.end, end);
+                Fragment{start, end}
+            }
+            Regex::Optional(inner) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                let frag = self.add_regex(inner);
+                self.add_epsilon(start, frag.start);
+                self.add_epsilon(start, end);
+                self.add_epsilon(frag.end, end);
+                Fragment{start, end}
+            }
+            Regex::Group{inner, ..} => self.add_regex(inner),
+            Regex::Assert(assertion) => {
+                let start = self.add_state();
+                let end = self.add_state();
+                self.assertions.push((start, *assertion, end));
+                Fragment{start, end}
+            }
+            Regex::Repeat{inner, min, max} => {
+                // A chain of min copies, then either a star or max-min copies
+                // that can each be skipped to the end, so x{2,4} is xx(x(x)?)?
+                let start = self.add_state();
+                let end = self.add_state();
+                let mut last = start;
+                for _ in 0..*min {
+                    let frag = self.add_regex(inner);
+                    self.add_epsilon(last, frag.start);
+                    last = frag.end;
+                }
+                match max {
+                    None => {
+                        let frag = self.add_regex(inner);
+                        self.add_epsilon(last, frag.start);
+                        self.add_epsilon(frag.end, frag.start);
+                        self.add_epsilon(frag.end, end);
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let frag = self.add_regex(inner);
+                            self.add_epsilon(last, end);
+                            self.add_epsilon(last, frag.start);
+                            last = frag.end;
+                        }
+                    }
+                }
+                self.add_epsilon(last, end);
+                Fragment{start, end}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{StateGraph, DFA};
+    use crate::testing::{regex_dfa, regex_graph};
+
+    /// Characters outside the alphabet of the RegEx can not match
+    fn check(graph: &StateGraph, accept: &[&str], reject: &[&str]) {
+        for s in accept.iter() {
+            assert!(graph.test_sentence(s).unwrap(), "{}", s);
+        }
+        for s in reject.iter() {
+            assert!(!graph.test_sentence(s).unwrap_or(false), "{}", s);
+        }
+    }
+
+    #[test]
+    fn star_of_alternation() {
+        let graph = regex_graph("(ab|c)*");
+        check(&graph, &["", "c", "ab", "abc", "cab", "ccabab"], &["a", "b", "ba", "abb", "aab", "cb"]);
+    }
+
+    #[test]
+    fn concat_and_empty() {
+        let graph = regex_graph("a(b)?c");
+        check(&graph, &["abc", "ac"], &["abbc"]);
+        let graph = regex_graph("");
+        check(&graph, &[""], &["a"]);
+    }
+
+    #[test]
+    fn plus_and_optional() {
+        let graph = regex_graph("ab+c?");
+        check(&graph, &["ab", "abbb", "abc", "abbc"], &["a", "ac", "abcc", "bc"]);
+        let graph = regex_graph("(a?b)+");
+        check(&graph, &["b", "ab", "bab", "abbb"], &["", "a", "aab", "ba"]);
+    }
+
+    #[test]
+    fn classes_and_escapes() {
+        let graph = regex_graph("[a-c0-9]+");
+        check(&graph, &["a", "c9", "000"], &["", "d", "a b"]);
+        let graph = regex_graph("[^a ]\\d");
+        check(&graph, &["b1", "91", "É1", "\u{1F600}1"], &["a1", " 1", "bb"]);
+        let graph = regex_graph("\\w+\\s.");
+        check(&graph, &["ab1 z", "x  ", "Z_\tж"], &["ab1", " x", "é x"]);
+        let graph = regex_graph("a\\*\\(\\\\");
+        check(&graph, &["a*(\\"], &["a", "aa*(\\", "a*("]);
+    }
+
+    #[test]
+    fn unicode() {
+        // The alphabet is a, b, the rest of a-z, ü and the ranges around them
+        let graph = regex_graph("(ab|[a-z]+)ü.");
+        assert_eq!(graph.alphabet.len(), 7);
+        check(&graph, &["abü中", "xyzüü", "qü\u{10FFFF}"], &["ü中", "abü", "ABü中"]);
+    }
+
+    #[test]
+    fn counted_repetition() {
+        let graph = regex_graph("(ab){2}");
+        check(&graph, &["abab"], &["", "ab", "ababab"]);
+        let graph = regex_graph("a{2,}b");
+        check(&graph, &["aab", "aaaaab"], &["b", "ab", "aa"]);
+        let graph = regex_graph("a{1,3}|b{0}");
+        check(&graph, &["", "a", "aa", "aaa"], &["aaaa", "b"]);
+
+        // The DFA for a{0,100} needs a state per count and one dead state
+        assert_eq!(regex_dfa("a{0,100}").minimize().0.transitions.len(), 102);
+    }
+
+    #[test]
+    fn near_size_limit() {
+        // Just under regex::MAX_SIZE, a state per a and one dead state
+        let dfa = regex_dfa("(a{1000}){49}");
+        assert_eq!(dfa.transitions.len(), 49002);
+        let graph = StateGraph::new_from_dfa(&dfa);
+        assert!(graph.test_sentence(&"a".repeat(49000)).unwrap());
+        assert!(!graph.test_sentence(&"a".repeat(48999)).unwrap());
+        assert_eq!(regex_dfa("(.{100}){100}|x").transitions.len(), 10003);
+    }
+
+    #[test]
+    fn assertions() {
+        let graph = regex_graph("^a*$");
+        check(&graph, &["", "aaa"], &["b"]);
+        let graph = regex_graph("a$b|^b");
+        check(&graph, &["b"], &["ab", "a"]);
+        let graph = regex_graph("(a|b)\\b(a| )*");
+        check(&graph, &["a", "a ", "b a"], &["aa", "ba", " "]);
+        let graph = regex_graph("a\\B.");
+        check(&graph, &["ab", "a_", "a1"], &["a ", "a-", "a"]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let dfa = DFA::from_regex_with_case("straße|[a-c]+é", true).unwrap();
+        let graph = StateGraph::new_from_dfa(&dfa);
+        check(&graph, &["STRAßE", "Straße", "aBcÉ", "cé"], &["STRASSE", "abd"]);
+        let graph = StateGraph::new_from_dfa(&DFA::from_regex_with_case("i|ı", true).unwrap());
+        check(&graph, &["i", "I", "ı"], &["İ"]);
+        let graph = StateGraph::new_from_dfa(&DFA::from_regex_with_case("I", true).unwrap());
+        check(&graph, &["i", "I"], &["ı"]);
+    }
+}
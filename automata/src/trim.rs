@@ -0,0 +1,122 @@
+//! Trimming useless states from a DFA
+//!
+//! [`DFA::validate`] only checks that every state number is in range. A
+//! state can still be unreachable, with no string leading to it from the
+//! start state, or dead, with no accept state reachable from it. Neither
+//! changes the language: unreachable states can be removed and the dead
+//! states reached can all be merged into a single sink state.
+
+use crate::DFA;
+use crate::graph::StateGraph;
+
+// *********************************************************************
+/// # States of a DFA that take no part in accepting a string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Useless {
+    /// States (1 relative) that can not be reached from the start state
+    pub unreachable: Vec<usize>,
+    /// States (1 relative) from which no accept state can be reached
+    pub dead: Vec<usize>,
+}
+
+// *********************************************************************
+/// Implement trimming on the DFA structure
+impl DFA {
+
+    /// Return the unreachable and dead states of a valid DFA
+    ///
+    /// A state can be both. The sink a partial DFA would be completed with
+    /// is not listed.
+    pub fn useless_states(&self) -> Useless {
+        let graph = StateGraph::new_from_dfa(self);
+        let reachable = graph.reachable_states();
+        let live = graph.live_states();
+        let states = 1..=self.transitions.len();
+        Useless{unreachable: states.clone().filter(|s| !reachable[s - 1]).collect(),
+                dead: states.filter(|s| !live[s - 1]).collect()}
+    }
+
+    // *********************************************************************
+    /// Return the DFA without unreachable states and with its reachable
+    /// dead states merged into one sink, accepting the same language
+    ///
+    /// The states left keep their order and are numbered from 1, with the
+    /// sink, if there is one, last. Also returns the new number of every
+    /// original state, None for a state that was removed. Undefined
+    /// transitions stay undefined.
+    pub fn trim(&self) -> (Box<DFA>, Vec<Option<usize>>) {
+
+        let useless = self.useless_states();
+        let reachable = |s: usize| !useless.unreachable.contains(&s);
+        let states = 1..=self.transitions.len();
+
+        // Number the useful states in order, then the sink
+        let mut numbers = vec![None; self.transitions.len()];
+        let mut count = 0;
+        for s in states.clone().filter(|&s| reachable(s) && !useless.dead.contains(&s)) {
+            count += 1;
+            numbers[s - 1] = Some(count);
+        }
+        let sunk: Vec<usize> = states.filter(|&s| reachable(s) && useless.dead.contains(&s)).collect();
+        for &s in sunk.iter() {
+            numbers[s - 1] = Some(count + 1);
+        }
+
+        let mut dfa = Box::new(DFA{alphabet: self.alphabet.clone(),
+                                   start: numbers[self.start - 1].expect("start is reachable"),
+                                   accept: vec!(),
+                                   transitions: vec!()});
+        for (n, row) in self.transitions.iter().enumerate() {
+            if numbers[n].is_some() && !sunk.contains(&(n + 1)) {
+                dfa.transitions.push(row.iter()
+                                        .map(|t| t.map(|t| numbers[t - 1].expect("reachable from a reachable state")))
+                                        .collect());
+                if self.accept.contains(&(n + 1)) {
+                    dfa.accept.push(numbers[n].unwrap());
+                }
+            }
+        }
+        if !sunk.is_empty() {
+            dfa.transitions.push(vec![Some(count + 1); self.alphabet.len()]);
+        }
+        (dfa, numbers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol;
+
+    #[test]
+    fn useless() {
+        // State 3 can not be reached, states 4 and 5 are dead
+        let dfa = DFA::new(vec!['a', 'b'], 1, vec![2],
+                           vec![vec![2, 4], vec![5, 2], vec![2, 1], vec![5, 4], vec![4, 4]]);
+        assert_eq!(dfa.useless_states(), Useless{unreachable: vec![3], dead: vec![4, 5]});
+
+        let (trimmed, numbers) = dfa.trim();
+        assert_eq!(numbers, vec![Some(1), Some(2), None, Some(3), Some(3)]);
+        assert_eq!(*trimmed, DFA::new(vec!['a', 'b'], 1, vec![2], vec![vec![2, 3], vec![3, 2], vec![3, 3]]));
+        assert!(trimmed.validate().is_ok());
+        assert!(trimmed.distinguish(&dfa).is_none());
+        assert_eq!(trimmed.trim().0, trimmed);
+    }
+
+    #[test]
+    fn partial_and_empty() {
+        // The undefined transitions stay undefined, state 3 is unreachable and dead
+        let dfa = DFA{alphabet: symbol::singles(&['a', 'b']), start: 1, accept: vec![2],
+                      transitions: vec![vec![Some(2), None], vec![None, Some(1)], vec![Some(3), None]]};
+        assert_eq!(dfa.useless_states(), Useless{unreachable: vec![3], dead: vec![3]});
+        let (trimmed, numbers) = dfa.trim();
+        assert_eq!(numbers, vec![Some(1), Some(2), None]);
+        assert_eq!(trimmed.transitions, vec![vec![Some(2), None], vec![None, Some(1)]]);
+
+        // Nothing is accepted, so everything left is the sink
+        let none = DFA::new(vec!['a'], 2, vec![], vec![vec![1], vec![1]]);
+        let (trimmed, numbers) = none.trim();
+        assert_eq!(numbers, vec![Some(1), Some(1)]);
+        assert_eq!(*trimmed, DFA::new(vec!['a'], 1, vec![], vec![vec![1]]));
+    }
+}